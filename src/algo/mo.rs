@@ -0,0 +1,477 @@
+//! Mo's algorithm
+//!
+//! 区間クエリをオフラインで並べ替え、区間の端を1つずつ伸縮させながら答える
+//! - `Mo::run`: 平方分割の順（奇数番目のブロックは右端を降順）。$O(N \sqrt{Q})$
+//! - `Mo::run_hilbert`: ヒルベルト曲線の順。N と Q が大きく異なるときに速いことが多い
+//! - `Mo::run_rollback`: 要素の追加とスナップショットへの巻き戻しだけで答える（削除が難しい場合）
+//! - `MoWithUpdates`: 一点更新を挟んだクエリ。$O(N^{5/3})$
+//!
+//! 区間の指定は `Segtree::prod` と同じく `RangeBounds` で行う
+
+use crate::data_struct::bit::range_to_pair;
+use std::ops::RangeBounds;
+
+/// 区間 $[l, r)$ の状態。区間は空の状態から始まる
+pub trait MoState {
+    type Answer;
+    /// 区間を $[i, r)$ に伸ばす（もとは $[i + 1, r)$）
+    fn add_left(&mut self, i: usize);
+    /// 区間を $[l, i + 1)$ に伸ばす（もとは $[l, i)$）
+    fn add_right(&mut self, i: usize);
+    /// 区間を $[i + 1, r)$ に縮める（もとは $[i, r)$）
+    fn remove_left(&mut self, i: usize);
+    /// 区間を $[l, i)$ に縮める（もとは $[l, i + 1)$）
+    fn remove_right(&mut self, i: usize);
+    fn answer(&self) -> Self::Answer;
+}
+
+/// 追加と巻き戻しだけで扱う区間の状態（`Mo::run_rollback` 用）
+pub trait RollbackMoState {
+    type Answer;
+    /// 区間を左に1つ伸ばす
+    fn add_left(&mut self, i: usize);
+    /// 区間を右に1つ伸ばす
+    fn add_right(&mut self, i: usize);
+    /// 現在の状態を記録する
+    fn snapshot(&mut self);
+    /// 直前の `snapshot` の状態に戻す
+    fn rollback(&mut self);
+    /// 区間を空にする
+    fn reset(&mut self);
+    fn answer(&self) -> Self::Answer;
+}
+
+/// 一点更新を挟む区間の状態（`MoWithUpdates` 用）
+pub trait MoUpdateState: MoState {
+    /// t 番目の更新を適用する。現在の区間は $[l, r)$
+    fn apply_update(&mut self, t: usize, l: usize, r: usize);
+    /// t 番目の更新を取り消す。現在の区間は $[l, r)$
+    fn revert_update(&mut self, t: usize, l: usize, r: usize);
+}
+
+#[derive(Clone, Debug)]
+pub struct Mo {
+    n: usize,
+    queries: Vec<(usize, usize)>,
+}
+
+impl Mo {
+    /// 長さ n の列に対するクエリを受け付ける
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            queries: Vec::new(),
+        }
+    }
+
+    /// クエリの個数
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// 区間 `range` のクエリを追加する。答えは追加した順に返る
+    pub fn add_query<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        self.queries.push((l, r));
+    }
+
+    pub fn run<S: MoState>(&self, state: &mut S) -> Vec<S::Answer> {
+        let block = self.block_size();
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_key(|&i| {
+            let (l, r) = self.queries[i];
+            let b = l / block;
+            (b, if b & 1 == 0 { r } else { self.n - r })
+        });
+        self.run_in_order(state, &order)
+    }
+
+    pub fn run_hilbert<S: MoState>(&self, state: &mut S) -> Vec<S::Answer> {
+        let log = (self.n + 1).next_power_of_two().trailing_zeros();
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_cached_key(|&i| {
+            let (l, r) = self.queries[i];
+            hilbert_order(l, r, log)
+        });
+        self.run_in_order(state, &order)
+    }
+
+    pub fn run_rollback<S: RollbackMoState>(&self, state: &mut S) -> Vec<S::Answer> {
+        let block = self.block_size();
+        let mut answers: Vec<Option<S::Answer>> = (0..self.queries.len()).map(|_| None).collect();
+        let mut blocks: Vec<Vec<usize>> = vec![Vec::new(); self.n / block + 1];
+        for (i, &(l, r)) in self.queries.iter().enumerate() {
+            if r - l <= block {
+                // 短い区間はそのまま伸ばして答える
+                state.reset();
+                for j in l..r {
+                    state.add_right(j);
+                }
+                answers[i] = Some(state.answer());
+            } else {
+                blocks[l / block].push(i);
+            }
+        }
+        for (b, qs) in blocks.iter_mut().enumerate() {
+            if qs.is_empty() {
+                continue;
+            }
+            qs.sort_by_key(|&i| self.queries[i].1);
+            // 右端はブロックの境界から伸ばしていき、左端は毎回境界から伸ばして巻き戻す
+            let border = (b + 1) * block;
+            state.reset();
+            let mut r = border;
+            for &i in qs.iter() {
+                let (ql, qr) = self.queries[i];
+                while r < qr {
+                    state.add_right(r);
+                    r += 1;
+                }
+                state.snapshot();
+                for j in (ql..border).rev() {
+                    state.add_left(j);
+                }
+                answers[i] = Some(state.answer());
+                state.rollback();
+            }
+        }
+        answers.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn block_size(&self) -> usize {
+        let q = self.queries.len().max(1) as f64;
+        ((self.n as f64 / q.sqrt()) as usize).max(1)
+    }
+
+    fn run_in_order<S: MoState>(&self, state: &mut S, order: &[usize]) -> Vec<S::Answer> {
+        let mut answers: Vec<Option<S::Answer>> = (0..self.queries.len()).map(|_| None).collect();
+        let (mut l, mut r) = (0, 0);
+        for &i in order {
+            let (ql, qr) = self.queries[i];
+            move_window(state, (&mut l, &mut r), (ql, qr));
+            answers[i] = Some(state.answer());
+        }
+        answers.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+/// 区間を先に伸ばしてから縮める
+fn move_window<S: MoState>(
+    state: &mut S,
+    (l, r): (&mut usize, &mut usize),
+    (ql, qr): (usize, usize),
+) {
+    while *l > ql {
+        *l -= 1;
+        state.add_left(*l);
+    }
+    while *r < qr {
+        state.add_right(*r);
+        *r += 1;
+    }
+    while *l < ql {
+        state.remove_left(*l);
+        *l += 1;
+    }
+    while *r > qr {
+        *r -= 1;
+        state.remove_right(*r);
+    }
+}
+
+/// $2^{log} \times 2^{log}$ の格子上でのヒルベルト曲線の順番
+fn hilbert_order(x: usize, y: usize, log: u32) -> u64 {
+    let (mut x, mut y) = (x as u64, y as u64);
+    let n = 1u64 << log;
+    let mut d = 0;
+    let mut s = n >> 1;
+    while s > 0 {
+        let rx = (x & s > 0) as u64;
+        let ry = (y & s > 0) as u64;
+        d += s * s * ((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+#[derive(Clone, Debug)]
+pub struct MoWithUpdates {
+    n: usize,
+    updates: usize,
+    // (l, r, それまでの更新の個数)
+    queries: Vec<(usize, usize, usize)>,
+}
+
+impl MoWithUpdates {
+    /// 長さ n の列に対するクエリを受け付ける
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            updates: 0,
+            queries: Vec::new(),
+        }
+    }
+
+    /// クエリの個数
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// 更新を1つ追加し、その番号（0-indexed）を返す
+    pub fn add_update(&mut self) -> usize {
+        self.updates += 1;
+        self.updates - 1
+    }
+
+    /// それまでに追加した更新をすべて適用した状態での、区間 `range` のクエリを追加する
+    pub fn add_query<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        self.queries.push((l, r, self.updates));
+    }
+
+    pub fn run<S: MoUpdateState>(&self, state: &mut S) -> Vec<S::Answer> {
+        let block = ((self.n.max(1) as f64).powf(2.0 / 3.0) as usize).max(1);
+        let mut order: Vec<usize> = (0..self.queries.len()).collect();
+        order.sort_by_key(|&i| {
+            let (l, r, t) = self.queries[i];
+            (l / block, r / block, t)
+        });
+        let mut answers: Vec<Option<S::Answer>> = (0..self.queries.len()).map(|_| None).collect();
+        let (mut l, mut r, mut t) = (0, 0, 0);
+        for &i in order.iter() {
+            let (ql, qr, qt) = self.queries[i];
+            move_window(state, (&mut l, &mut r), (ql, qr));
+            while t < qt {
+                state.apply_update(t, l, r);
+                t += 1;
+            }
+            while t > qt {
+                t -= 1;
+                state.revert_update(t, l, r);
+            }
+            answers[i] = Some(state.answer());
+        }
+        answers.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 区間内の値の種類数
+    struct Distinct {
+        a: Vec<usize>,
+        cnt: Vec<usize>,
+        distinct: usize,
+        // (位置, 値)。適用するたびに値を入れ替える
+        updates: Vec<(usize, usize)>,
+    }
+
+    impl Distinct {
+        fn new(a: Vec<usize>, updates: Vec<(usize, usize)>) -> Self {
+            let m = a
+                .iter()
+                .chain(updates.iter().map(|(_, x)| x))
+                .max()
+                .map_or(0, |x| x + 1);
+            Self {
+                a,
+                cnt: vec![0; m],
+                distinct: 0,
+                updates,
+            }
+        }
+
+        fn add(&mut self, x: usize) {
+            self.cnt[x] += 1;
+            if self.cnt[x] == 1 {
+                self.distinct += 1;
+            }
+        }
+
+        fn remove(&mut self, x: usize) {
+            self.cnt[x] -= 1;
+            if self.cnt[x] == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        fn swap_update(&mut self, t: usize, l: usize, r: usize) {
+            let (p, x) = self.updates[t];
+            if l <= p && p < r {
+                self.remove(self.a[p]);
+                self.add(x);
+            }
+            self.updates[t].1 = std::mem::replace(&mut self.a[p], x);
+        }
+    }
+
+    impl MoState for Distinct {
+        type Answer = usize;
+        fn add_left(&mut self, i: usize) {
+            self.add(self.a[i]);
+        }
+        fn add_right(&mut self, i: usize) {
+            self.add(self.a[i]);
+        }
+        fn remove_left(&mut self, i: usize) {
+            self.remove(self.a[i]);
+        }
+        fn remove_right(&mut self, i: usize) {
+            self.remove(self.a[i]);
+        }
+        fn answer(&self) -> usize {
+            self.distinct
+        }
+    }
+
+    impl MoUpdateState for Distinct {
+        fn apply_update(&mut self, t: usize, l: usize, r: usize) {
+            self.swap_update(t, l, r);
+        }
+        fn revert_update(&mut self, t: usize, l: usize, r: usize) {
+            self.swap_update(t, l, r);
+        }
+    }
+
+    /// 区間内の最頻値の出現回数（追加と巻き戻しだけで扱う）
+    struct MaxFrequency<'a> {
+        a: &'a [usize],
+        cnt: Vec<usize>,
+        max: usize,
+        history: Vec<usize>,
+        saved: (usize, usize),
+    }
+
+    impl RollbackMoState for MaxFrequency<'_> {
+        type Answer = usize;
+        fn add_left(&mut self, i: usize) {
+            self.add_right(i);
+        }
+        fn add_right(&mut self, i: usize) {
+            let x = self.a[i];
+            self.cnt[x] += 1;
+            self.max = self.max.max(self.cnt[x]);
+            self.history.push(x);
+        }
+        fn snapshot(&mut self) {
+            self.saved = (self.history.len(), self.max);
+        }
+        fn rollback(&mut self) {
+            while self.history.len() > self.saved.0 {
+                let x = self.history.pop().unwrap();
+                self.cnt[x] -= 1;
+            }
+            self.max = self.saved.1;
+        }
+        fn reset(&mut self) {
+            self.saved = (0, 0);
+            self.rollback();
+        }
+        fn answer(&self) -> usize {
+            self.max
+        }
+    }
+
+    fn naive_distinct(a: &[usize]) -> usize {
+        let mut b = a.to_vec();
+        b.sort_unstable();
+        b.dedup();
+        b.len()
+    }
+
+    #[test]
+    fn mo_test() {
+        let mut rng = StdRng::seed_from_u64(39);
+        for n in 0..40 {
+            let a: Vec<usize> = (0..n).map(|_| rng.random_range(0..8)).collect();
+            let mut mo = Mo::new(n);
+            let mut expected = Vec::new();
+            for _ in 0..50 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                mo.add_query(l..r);
+                expected.push(naive_distinct(&a[l..r]));
+            }
+            assert_eq!(mo.run(&mut Distinct::new(a.clone(), vec![])), expected);
+            assert_eq!(
+                mo.run_hilbert(&mut Distinct::new(a.clone(), vec![])),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn rollback_mo_test() {
+        let mut rng = StdRng::seed_from_u64(40);
+        for n in 0..40 {
+            let a: Vec<usize> = (0..n).map(|_| rng.random_range(0..5)).collect();
+            let mut mo = Mo::new(n);
+            let mut expected = Vec::new();
+            for _ in 0..50 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                mo.add_query(l..r);
+                let max = (0..5)
+                    .map(|x| a[l..r].iter().filter(|&&y| y == x).count())
+                    .max();
+                expected.push(max.unwrap());
+            }
+            let mut state = MaxFrequency {
+                a: &a,
+                cnt: vec![0; 5],
+                max: 0,
+                history: Vec::new(),
+                saved: (0, 0),
+            };
+            assert_eq!(mo.run_rollback(&mut state), expected);
+        }
+    }
+
+    #[test]
+    fn mo_with_updates_test() {
+        let mut rng = StdRng::seed_from_u64(41);
+        for n in 1..40 {
+            let a: Vec<usize> = (0..n).map(|_| rng.random_range(0..8)).collect();
+            let mut b = a.clone();
+            let mut mo = MoWithUpdates::new(n);
+            let mut updates = Vec::new();
+            let mut expected = Vec::new();
+            for _ in 0..80 {
+                if rng.random_bool(0.4) {
+                    let p = rng.random_range(0..n);
+                    let x = rng.random_range(0..8);
+                    assert_eq!(mo.add_update(), updates.len());
+                    updates.push((p, x));
+                    b[p] = x;
+                } else {
+                    let l = rng.random_range(0..=n);
+                    let r = rng.random_range(l..=n);
+                    mo.add_query(l..r);
+                    expected.push(naive_distinct(&b[l..r]));
+                }
+            }
+            assert_eq!(mo.run(&mut Distinct::new(a, updates)), expected);
+        }
+    }
+}
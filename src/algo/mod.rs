@@ -1 +1,2 @@
-pub mod binary_search;
\ No newline at end of file
+pub mod binary_search;
+pub mod mo;
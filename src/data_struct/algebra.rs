@@ -0,0 +1,304 @@
+//! データ構造に載せる代数的構造
+//!
+//! 値の型 `S` と演算を型レベルで束ねる。同じ `i64` に対しても
+//! `Additive<i64>` と `Xor<i64>` のように異なる演算を選べる。
+
+use crate::math::modint::ModInt;
+use num::{Bounded, Integer, One, Unsigned, Zero};
+use std::marker::PhantomData;
+
+/// モノイド（結合則を満たす二項演算と単位元）
+pub trait Monoid {
+    type S: Clone;
+    fn identity() -> Self::S;
+    fn op(a: &Self::S, b: &Self::S) -> Self::S;
+}
+
+/// 群（逆元を持つモノイド）
+///
+/// BIT などは可換性も仮定するので、非可換な群を載せるときは各データ構造のドキュメントを確認すること
+pub trait Group: Monoid {
+    fn inverse(a: &Self::S) -> Self::S;
+}
+
+/// 加法
+pub struct Additive<T>(PhantomData<T>);
+
+/// ビットごとの XOR
+pub struct Xor<T>(PhantomData<T>);
+
+macro_rules! impl_signed {
+    ($($t: ty),*) => {$(
+        impl Monoid for Additive<$t> {
+            type S = $t;
+            fn identity() -> $t {
+                0
+            }
+            fn op(a: &$t, b: &$t) -> $t {
+                a + b
+            }
+        }
+        impl Group for Additive<$t> {
+            fn inverse(a: &$t) -> $t {
+                -a
+            }
+        }
+    )*};
+}
+impl_signed!(i8, i16, i32, i64, i128, isize);
+
+// 符号なし整数は 2^BITS を法とする群とみなす。
+// 途中で一時的にオーバーフローしても、区間和が型に収まっていれば正しい値になる。
+macro_rules! impl_unsigned {
+    ($($t: ty),*) => {$(
+        impl Monoid for Additive<$t> {
+            type S = $t;
+            fn identity() -> $t {
+                0
+            }
+            fn op(a: &$t, b: &$t) -> $t {
+                a.wrapping_add(*b)
+            }
+        }
+        impl Group for Additive<$t> {
+            fn inverse(a: &$t) -> $t {
+                a.wrapping_neg()
+            }
+        }
+    )*};
+}
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_xor {
+    ($($t: ty),*) => {$(
+        impl Monoid for Xor<$t> {
+            type S = $t;
+            fn identity() -> $t {
+                0
+            }
+            fn op(a: &$t, b: &$t) -> $t {
+                a ^ b
+            }
+        }
+        impl Group for Xor<$t> {
+            fn inverse(a: &$t) -> $t {
+                *a
+            }
+        }
+    )*};
+}
+impl_xor!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl<const MOD: u32> Monoid for Additive<ModInt<MOD>> {
+    type S = ModInt<MOD>;
+    fn identity() -> ModInt<MOD> {
+        ModInt::new(0)
+    }
+    fn op(a: &ModInt<MOD>, b: &ModInt<MOD>) -> ModInt<MOD> {
+        *a + *b
+    }
+}
+
+impl<const MOD: u32> Group for Additive<ModInt<MOD>> {
+    fn inverse(a: &ModInt<MOD>) -> ModInt<MOD> {
+        -*a
+    }
+}
+
+/// 冪等なモノイド（`op(a, a) == a`）
+///
+/// 区間が重なっても積が変わらないので、Sparse Table に載せられる
+pub trait Idempotent: Monoid {}
+
+/// 最小値
+pub struct Min<T>(PhantomData<T>);
+
+/// 最大値
+pub struct Max<T>(PhantomData<T>);
+
+/// 最大公約数
+///
+/// 符号付き整数では $\gcd(a, a) = |a|$ となり冪等でないので、`Idempotent` は符号なし整数のみ
+pub struct Gcd<T>(PhantomData<T>);
+
+/// 一次関数 $x \mapsto ax + b$ の合成
+///
+/// 元 `(a, b)` は $x \mapsto ax + b$ を表し、`op(f, g)` は f を適用してから g を適用する関数を返す
+pub struct Affine<T>(PhantomData<T>);
+
+impl<T: PartialOrd + Clone + Bounded> Monoid for Min<T> {
+    type S = T;
+    fn identity() -> T {
+        T::max_value()
+    }
+    fn op(a: &T, b: &T) -> T {
+        if a <= b {
+            a.clone()
+        } else {
+            b.clone()
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Monoid for Max<T> {
+    type S = T;
+    fn identity() -> T {
+        T::min_value()
+    }
+    fn op(a: &T, b: &T) -> T {
+        if a >= b {
+            a.clone()
+        } else {
+            b.clone()
+        }
+    }
+}
+
+impl<T: Integer + Clone> Monoid for Gcd<T> {
+    type S = T;
+    fn identity() -> T {
+        T::zero()
+    }
+    fn op(a: &T, b: &T) -> T {
+        a.gcd(b)
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Idempotent for Min<T> {}
+impl<T: PartialOrd + Clone + Bounded> Idempotent for Max<T> {}
+impl<T: Integer + Unsigned + Clone> Idempotent for Gcd<T> {}
+
+impl<T> Monoid for Affine<T>
+where
+    T: Zero + One + Clone,
+{
+    type S = (T, T);
+    fn identity() -> (T, T) {
+        (T::one(), T::zero())
+    }
+    fn op(f: &(T, T), g: &(T, T)) -> (T, T) {
+        let (a1, b1) = f.clone();
+        let (a2, b2) = g.clone();
+        (a1 * a2.clone(), a2 * b1 + b2)
+    }
+}
+
+/// 値の代入。`None` は何もしない作用を表し、`op(f, g)` は後から適用した g を優先する
+pub struct Assign<T>(PhantomData<T>);
+
+/// 区間の長さ付きの総和
+///
+/// 元 `(sum, len)` は総和と要素数の組。区間への一様な作用で総和を更新するのに使う
+pub struct SumWithLen<T>(PhantomData<T>);
+
+impl<T: Clone> Monoid for Assign<T> {
+    type S = Option<T>;
+    fn identity() -> Option<T> {
+        None
+    }
+    fn op(f: &Option<T>, g: &Option<T>) -> Option<T> {
+        g.clone().or_else(|| f.clone())
+    }
+}
+
+impl<T> Monoid for SumWithLen<T>
+where
+    T: Zero + Clone,
+{
+    type S = (T, T);
+    fn identity() -> (T, T) {
+        (T::zero(), T::zero())
+    }
+    fn op(a: &(T, T), b: &(T, T)) -> (T, T) {
+        (a.0.clone() + b.0.clone(), a.1.clone() + b.1.clone())
+    }
+}
+
+/// モノイド `M` への作用
+///
+/// `act(f, x)` は x に f を作用させた値を返す。以下を満たすこと
+/// - `act(identity, x) == x`
+/// - `act(op(f, g), x) == act(g, act(f, x))`（`op(f, g)` は f, g の順に作用させる）
+/// - `act(f, M::op(x, y)) == M::op(act(f, x), act(f, y))`
+pub trait Action<M: Monoid>: Monoid {
+    fn act(f: &Self::S, x: &M::S) -> M::S;
+}
+
+// 単位元（番兵）に加算すると溢れるので、単位元はそのまま返す
+impl<T> Action<Min<T>> for Additive<T>
+where
+    T: PartialOrd + Clone + Bounded,
+    Additive<T>: Monoid<S = T>,
+{
+    fn act(f: &T, x: &T) -> T {
+        if *x == T::max_value() {
+            x.clone()
+        } else {
+            Self::op(x, f)
+        }
+    }
+}
+
+impl<T> Action<Max<T>> for Additive<T>
+where
+    T: PartialOrd + Clone + Bounded,
+    Additive<T>: Monoid<S = T>,
+{
+    fn act(f: &T, x: &T) -> T {
+        if *x == T::min_value() {
+            x.clone()
+        } else {
+            Self::op(x, f)
+        }
+    }
+}
+
+impl<T> Action<SumWithLen<T>> for Additive<T>
+where
+    T: Zero + Clone + std::ops::Mul<Output = T>,
+    Additive<T>: Monoid<S = T>,
+{
+    fn act(f: &T, x: &(T, T)) -> (T, T) {
+        (Self::op(&x.0, &(f.clone() * x.1.clone())), x.1.clone())
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Action<Min<T>> for Min<T> {
+    fn act(f: &T, x: &T) -> T {
+        Self::op(f, x)
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Action<Max<T>> for Max<T> {
+    fn act(f: &T, x: &T) -> T {
+        Self::op(f, x)
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Action<Min<T>> for Assign<T> {
+    fn act(f: &Option<T>, x: &T) -> T {
+        f.clone().unwrap_or_else(|| x.clone())
+    }
+}
+
+impl<T: PartialOrd + Clone + Bounded> Action<Max<T>> for Assign<T> {
+    fn act(f: &Option<T>, x: &T) -> T {
+        f.clone().unwrap_or_else(|| x.clone())
+    }
+}
+
+impl<T: Zero + Clone + std::ops::Mul<Output = T>> Action<SumWithLen<T>> for Assign<T> {
+    fn act(f: &Option<T>, x: &(T, T)) -> (T, T) {
+        match f {
+            Some(v) => (v.clone() * x.1.clone(), x.1.clone()),
+            None => x.clone(),
+        }
+    }
+}
+
+impl<T: Zero + One + Clone> Action<SumWithLen<T>> for Affine<T> {
+    fn act(f: &(T, T), x: &(T, T)) -> (T, T) {
+        let (a, b) = f.clone();
+        (a * x.0.clone() + b * x.1.clone(), x.1.clone())
+    }
+}
@@ -0,0 +1,265 @@
+//! Binary Trie
+//!
+//! $2^{BITS}$ 未満の非負整数の多重集合を、上位ビットからの二分木で管理する。
+//! 各操作は$O(BITS)$
+//! - 追加・削除（個数つき）と個数の取得
+//! - 全要素に x を XOR する（遅延評価で$O(1)$）
+//! - x との XOR の最小値・最大値、XOR した後の k 番目の値、x 未満の個数
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    // 子の添字。0 はまだ確保していないことを表す（0 は根なので子にはならない）
+    child: [usize; 2],
+    count: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct BinaryTrie<const BITS: usize> {
+    nodes: Vec<Node>,
+    // 全要素に XOR されている値
+    lazy: u64,
+}
+
+impl<const BITS: usize> Default for BinaryTrie<BITS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BITS: usize> BinaryTrie<BITS> {
+    pub fn new() -> Self {
+        assert!(BITS <= 64);
+        Self {
+            nodes: vec![Node {
+                child: [0; 2],
+                count: 0,
+            }],
+            lazy: 0,
+        }
+    }
+
+    /// 要素数（重複を含む）
+    pub fn len(&self) -> usize {
+        self.nodes[0].count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert(&mut self, x: u64) {
+        self.insert_n(x, 1);
+    }
+
+    /// x を n 個追加する
+    pub fn insert_n(&mut self, x: u64, n: usize) {
+        self.check(x);
+        let v = x ^ self.lazy;
+        let mut k = 0;
+        self.nodes[k].count += n;
+        for b in (0..BITS).rev() {
+            let bit = (v >> b & 1) as usize;
+            if self.nodes[k].child[bit] == 0 {
+                self.nodes.push(Node {
+                    child: [0; 2],
+                    count: 0,
+                });
+                self.nodes[k].child[bit] = self.nodes.len() - 1;
+            }
+            k = self.nodes[k].child[bit];
+            self.nodes[k].count += n;
+        }
+    }
+
+    /// x を1個削除する。x が含まれていなければ false を返す
+    pub fn erase(&mut self, x: u64) -> bool {
+        self.erase_n(x, 1) == 1
+    }
+
+    /// x を最大 n 個削除し、実際に削除した個数を返す
+    pub fn erase_n(&mut self, x: u64, n: usize) -> usize {
+        let n = n.min(self.count(x));
+        if n == 0 {
+            return 0;
+        }
+        let v = x ^ self.lazy;
+        let mut k = 0;
+        self.nodes[k].count -= n;
+        for b in (0..BITS).rev() {
+            k = self.nodes[k].child[(v >> b & 1) as usize];
+            self.nodes[k].count -= n;
+        }
+        n
+    }
+
+    /// x の個数
+    pub fn count(&self, x: u64) -> usize {
+        self.check(x);
+        let v = x ^ self.lazy;
+        let mut k = 0;
+        for b in (0..BITS).rev() {
+            k = self.nodes[k].child[(v >> b & 1) as usize];
+            if k == 0 {
+                return 0;
+            }
+        }
+        self.nodes[k].count
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        self.count(x) > 0
+    }
+
+    /// 全要素に x を XOR する
+    pub fn xor_all(&mut self, x: u64) {
+        self.check(x);
+        self.lazy ^= x;
+    }
+
+    /// 各要素に `xor_mask` を XOR したときの、小さい方から k 番目（0-indexed）の値
+    pub fn kth(&self, k: usize, xor_mask: u64) -> Option<u64> {
+        self.check(xor_mask);
+        if k >= self.len() {
+            return None;
+        }
+        let m = xor_mask ^ self.lazy;
+        let (mut k, mut node, mut res) = (k, 0, 0);
+        for b in (0..BITS).rev() {
+            let bit = (m >> b & 1) as usize;
+            let c = self.count_at(self.nodes[node].child[bit]);
+            if k < c {
+                node = self.nodes[node].child[bit];
+            } else {
+                k -= c;
+                node = self.nodes[node].child[bit ^ 1];
+                res |= 1 << b;
+            }
+        }
+        Some(res)
+    }
+
+    /// 要素と x の XOR の最小値
+    pub fn min_xor(&self, x: u64) -> Option<u64> {
+        self.kth(0, x)
+    }
+
+    /// 要素と x の XOR の最大値
+    pub fn max_xor(&self, x: u64) -> Option<u64> {
+        self.check(x);
+        self.kth(self.len().checked_sub(1)?, x)
+    }
+
+    /// 各要素に `xor_mask` を XOR したときに x 未満となる要素の個数
+    pub fn count_less(&self, x: u64, xor_mask: u64) -> usize {
+        self.check(xor_mask);
+        let m = xor_mask ^ self.lazy;
+        if BITS < 64 && x >> BITS > 0 {
+            return self.len();
+        }
+        let (mut node, mut res) = (0, 0);
+        for b in (0..BITS).rev() {
+            let bit = (m >> b & 1) as usize;
+            if x >> b & 1 == 1 {
+                res += self.count_at(self.nodes[node].child[bit]);
+                node = self.nodes[node].child[bit ^ 1];
+            } else {
+                node = self.nodes[node].child[bit];
+            }
+            if node == 0 {
+                break;
+            }
+        }
+        res
+    }
+
+    fn count_at(&self, k: usize) -> usize {
+        if k == 0 {
+            0
+        } else {
+            self.nodes[k].count
+        }
+    }
+
+    fn check(&self, x: u64) {
+        assert!(BITS == 64 || x >> BITS == 0, "x must be less than 2^BITS");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn binary_trie_test() {
+        let mut rng = StdRng::seed_from_u64(36);
+        let mut trie = BinaryTrie::<5>::new();
+        let mut naive: Vec<u64> = Vec::new();
+        for _ in 0..3000 {
+            let x = rng.random_range(0..32);
+            match rng.random_range(0..4) {
+                0 => {
+                    let n = rng.random_range(1..=3);
+                    trie.insert_n(x, n);
+                    naive.extend(std::iter::repeat(x).take(n));
+                }
+                1 => {
+                    let n = rng.random_range(1..=3);
+                    let removed = naive.iter().filter(|&&y| y == x).count().min(n);
+                    assert_eq!(trie.erase_n(x, n), removed);
+                    for _ in 0..removed {
+                        let pos = naive.iter().position(|&y| y == x).unwrap();
+                        naive.swap_remove(pos);
+                    }
+                }
+                2 => {
+                    trie.xor_all(x);
+                    naive.iter_mut().for_each(|y| *y ^= x);
+                }
+                _ => {
+                    let expected = naive.contains(&x);
+                    assert_eq!(trie.erase(x), expected);
+                    if let Some(pos) = naive.iter().position(|&y| y == x) {
+                        naive.swap_remove(pos);
+                    }
+                }
+            }
+            assert_eq!(trie.len(), naive.len());
+            let x = rng.random_range(0..32);
+            assert_eq!(trie.count(x), naive.iter().filter(|&&y| y == x).count());
+            assert_eq!(trie.min_xor(x), naive.iter().map(|&y| y ^ x).min());
+            assert_eq!(trie.max_xor(x), naive.iter().map(|&y| y ^ x).max());
+            let mask = rng.random_range(0..32);
+            let mut sorted: Vec<u64> = naive.iter().map(|&y| y ^ mask).collect();
+            sorted.sort_unstable();
+            for k in 0..=sorted.len() {
+                assert_eq!(trie.kth(k, mask), sorted.get(k).copied());
+            }
+            for x in 0..=40 {
+                let expected = sorted.iter().filter(|&&y| y < x).count();
+                assert_eq!(trie.count_less(x, mask), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn binary_trie_64_test() {
+        let mut trie = BinaryTrie::<64>::new();
+        trie.insert(u64::MAX);
+        trie.insert(0);
+        assert_eq!(trie.max_xor(0), Some(u64::MAX));
+        assert_eq!(trie.min_xor(1 << 63), Some((1 << 63) - 1));
+        trie.xor_all(u64::MAX);
+        assert_eq!(trie.kth(0, 0), Some(0));
+        assert_eq!(trie.count_less(u64::MAX, 0), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn binary_trie_out_of_range_test() {
+        let mut trie = BinaryTrie::<5>::new();
+        trie.insert(3);
+        trie.max_xor(1 << 5);
+    }
+}
@@ -1,46 +1,318 @@
 //! Binary Indexed Tree
 //!
-//! 数列 $a_1, a_2, a_3, \cdots , a_n$ が与えられたとき以下の様なことができる
+//! 可換群 `G` の元の列 $a_1, a_2, a_3, \cdots , a_n$ が与えられたとき以下の様なことができる
 //! - iとxが与えられたとき、$a_i$にxを加算する
-//! - iが与えられたとき、$a_1 + a_2 + a_3 + \cdots + a_n$を求める
+//! - iが与えられたとき、$a_1 + a_2 + a_3 + \cdots + a_i$を求める
+//! - 区間 $[l, r)$ が与えられたとき、$a_l + \cdots + a_{r-1}$を求める
 //! - 計算量は$O(log(N))$
+//!
+//! 各要素が非負（累積和が単調）なら、累積和が w 以上になる最小の位置を二分探索せずに
+//! $O(log(N))$ で求められる。要素を値ごとの個数とみなせば順序付き多重集合（`BITMultiset`）として使える
 
-type T = usize;
-#[derive(Clone, Debug)]
-pub struct BIT {
-    size: usize,
-    bit: Vec<T>,
+use super::algebra::{Additive, Group};
+use std::ops::{Bound, RangeBounds};
+
+pub struct BIT<G: Group = Additive<usize>> {
+    n: usize,
+    bit: Vec<G::S>,
 }
 
-impl BIT {
-    pub fn new(n: usize) -> BIT {
-        BIT {
-            size: n + 1,
-            bit: vec![0; n + 1],
+impl<G: Group> Clone for BIT<G> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            bit: self.bit.clone(),
         }
     }
+}
 
-    pub fn add(&mut self, i: usize, x: T) {
-        let mut index = i as isize;
-        while index < self.size as isize {
-            self.bit[index as usize] += x;
-            index += index & -index;
+impl<G: Group> std::fmt::Debug for BIT<G>
+where
+    G::S: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("BIT").field("n", &self.n).field("bit", &self.bit).finish()
+    }
+}
+
+impl<G: Group> BIT<G> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            bit: vec![G::identity(); n + 1],
         }
     }
 
-    pub fn sum(&mut self, i: usize) -> T {
-        let mut s = 0 as T;
-        let mut index = i as isize;
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_i$ に x を加算する（$1 \leq i \leq n$）
+    pub fn add(&mut self, i: usize, x: G::S) {
+        assert!(1 <= i && i <= self.n);
+        let mut index = i;
+        while index <= self.n {
+            self.bit[index] = G::op(&self.bit[index], &x);
+            index += index & index.wrapping_neg();
+        }
+    }
+
+    /// $a_1 + \cdots + a_i$ を求める（$0 \leq i \leq n$）
+    pub fn sum(&self, i: usize) -> G::S {
+        assert!(i <= self.n);
+        let mut s = G::identity();
+        let mut index = i;
         while index > 0 {
-            s += self.bit[index as usize];
-            index -= index & -index;
+            s = G::op(&s, &self.bit[index]);
+            index -= index & index.wrapping_neg();
         }
         s
     }
 
-    #[deprecated = "テストしてない、ちゃんと動く保証はない"]
-    pub fn sum_range(&mut self, range: std::ops::Range<usize>) -> T {
-        let std::ops::Range { start, end } = range;
-        self.sum(end - 1) - self.sum(start - 1)
+    /// 区間 `range` の総和を求める
+    ///
+    /// 添字は `add` と同じく 1 から数える。`..` は $a_1 + \cdots + a_n$ になる
+    pub fn sum_range<R: RangeBounds<usize>>(&self, range: R) -> G::S {
+        let l = match range.start_bound() {
+            Bound::Included(&l) => l,
+            Bound::Excluded(&l) => l + 1,
+            Bound::Unbounded => 1,
+        };
+        let (_, r) = range_to_pair(range, self.n + 1);
+        assert!(1 <= l && l <= r && r <= self.n + 1);
+        G::op(&self.sum(r - 1), &G::inverse(&self.sum(l - 1)))
+    }
+
+    /// `f(&self.sum(i))` が true となる最大の i を求める
+    ///
+    /// f は累積和に対して単調（true が続いたあと false が続く）で、`f(&G::identity())` は true であること
+    pub fn max_right<F: Fn(&G::S) -> bool>(&self, f: F) -> usize {
+        let mut pos = 0;
+        let mut s = G::identity();
+        let mut step = if self.n == 0 {
+            0
+        } else {
+            1 << (usize::BITS - 1 - self.n.leading_zeros())
+        };
+        while step > 0 {
+            if pos + step <= self.n {
+                let t = G::op(&s, &self.bit[pos + step]);
+                if f(&t) {
+                    pos += step;
+                    s = t;
+                }
+            }
+            step >>= 1;
+        }
+        pos
+    }
+
+    /// $a_1 + \cdots + a_i \geq w$ となる最小の $i \geq 1$ を求める（存在しなければ n + 1）
+    ///
+    /// 各要素が非負であること
+    pub fn lower_bound(&self, w: &G::S) -> usize
+    where
+        G::S: PartialOrd,
+    {
+        self.max_right(|s| s < w) + 1
+    }
+}
+
+impl BIT<Additive<usize>> {
+    /// 各要素を個数とみなしたとき、小さい方から k 番目（0-indexed）の要素がある位置 i を求める
+    pub fn kth(&self, k: usize) -> Option<usize> {
+        let i = self.lower_bound(&(k + 1));
+        if i <= self.n {
+            Some(i)
+        } else {
+            None
+        }
+    }
+}
+
+/// $[0, n)$ の整数を要素に持つ多重集合
+///
+/// 挿入・削除・k 番目の値・x 未満の要素数が$O(log(N))$
+#[derive(Clone, Debug)]
+pub struct BITMultiset {
+    len: usize,
+    count: Vec<usize>,
+    bit: BIT,
+}
+
+impl BITMultiset {
+    pub fn new(n: usize) -> Self {
+        Self {
+            len: 0,
+            count: vec![0; n],
+            bit: BIT::new(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, x: usize) {
+        self.count[x] += 1;
+        self.len += 1;
+        self.bit.add(x + 1, 1);
+    }
+
+    /// x を1つ削除する。x が含まれていなければ false を返す
+    pub fn erase(&mut self, x: usize) -> bool {
+        if self.count[x] == 0 {
+            return false;
+        }
+        self.count[x] -= 1;
+        self.len -= 1;
+        self.bit.add(x + 1, Additive::<usize>::inverse(&1));
+        true
+    }
+
+    pub fn count(&self, x: usize) -> usize {
+        self.count[x]
+    }
+
+    /// x 未満の要素数
+    pub fn count_less(&self, x: usize) -> usize {
+        self.bit.sum(x.min(self.count.len()))
+    }
+
+    /// 小さい方から k 番目（0-indexed）の要素
+    pub fn kth(&self, k: usize) -> Option<usize> {
+        self.bit.kth(k).map(|i| i - 1)
+    }
+}
+
+/// `RangeBounds` を半開区間 $[l, r)$ に変換する
+pub(crate) fn range_to_pair<R: RangeBounds<usize>>(range: R, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => n,
+    };
+    (l, r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Xor;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn bit_test() {
+        let mut bit = BIT::<Additive<i64>>::new(5);
+        let a = [3, -1, 4, -1, 5];
+        for (i, &x) in a.iter().enumerate() {
+            bit.add(i + 1, x);
+        }
+        assert_eq!(bit.sum(0), 0);
+        assert_eq!(bit.sum(3), 6);
+        assert_eq!(bit.sum_range(..), 10);
+        assert_eq!(bit.sum_range(1..3), 2);
+        assert_eq!(bit.sum_range(2..=4), 2);
+        assert_eq!(bit.sum_range(3..), 8);
+        for l in 1..=6 {
+            for r in l..=6 {
+                assert_eq!(bit.sum_range(l..r), a[l - 1..r - 1].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn bit_usize_test() {
+        let mut bit: BIT = BIT::new(4);
+        bit.add(1, 5);
+        bit.add(4, 2);
+        assert_eq!(bit.sum_range(1..2), 5);
+        assert_eq!(bit.sum_range(2..5), 2);
+        assert_eq!(bit.sum_range(2..4), 0);
+    }
+
+    #[test]
+    fn bit_xor_test() {
+        let mut bit = BIT::<Xor<u32>>::new(4);
+        bit.add(1, 0b101);
+        bit.add(3, 0b110);
+        bit.add(4, 0b011);
+        assert_eq!(bit.sum_range(1..4), 0b011);
+        assert_eq!(bit.sum_range(3..5), 0b101);
+    }
+
+    #[test]
+    fn lower_bound_test() {
+        let a = [2i64, 0, 3, 1, 0, 4];
+        let mut bit = BIT::<Additive<i64>>::new(a.len());
+        for (i, &x) in a.iter().enumerate() {
+            bit.add(i + 1, x);
+        }
+        for w in -1..=12 {
+            let expected = (1..=a.len())
+                .find(|&i| a[..i].iter().sum::<i64>() >= w)
+                .unwrap_or(a.len() + 1);
+            assert_eq!(bit.lower_bound(&w), expected);
+        }
+        assert_eq!(bit.max_right(|&s| s <= 5), 3);
+        assert_eq!(bit.max_right(|&s| s <= 6), 5);
+        assert_eq!(BIT::<Additive<i64>>::new(0).lower_bound(&1), 1);
+    }
+
+    #[test]
+    fn multiset_test() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let n = 16;
+        let mut set = BITMultiset::new(n);
+        let mut naive: Vec<usize> = vec![];
+        for _ in 0..1000 {
+            let x = rng.random_range(0..n);
+            match rng.random_range(0..3) {
+                0 => {
+                    set.insert(x);
+                    naive.push(x);
+                    naive.sort();
+                }
+                1 => {
+                    let pos = naive.iter().position(|&y| y == x);
+                    assert_eq!(set.erase(x), pos.is_some());
+                    if let Some(pos) = pos {
+                        naive.remove(pos);
+                    }
+                }
+                _ => {
+                    assert_eq!(set.count(x), naive.iter().filter(|&&y| y == x).count());
+                    assert_eq!(set.count_less(x), naive.iter().filter(|&&y| y < x).count());
+                }
+            }
+            assert_eq!(set.len(), naive.len());
+            let k = rng.random_range(0..n);
+            assert_eq!(set.kth(k), naive.get(k).copied());
+        }
+    }
+
+    #[test]
+    fn bit_modint_test() {
+        type Mint = ModInt998244353;
+        let mut bit = BIT::<Additive<Mint>>::new(3);
+        bit.add(1, Mint::new(998_244_352));
+        bit.add(2, Mint::new(5));
+        assert_eq!(bit.sum(2).val(), 4);
+        assert_eq!(bit.sum_range(2..).val(), 5);
     }
 }
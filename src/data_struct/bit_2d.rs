@@ -0,0 +1,252 @@
+//! 2次元 Binary Indexed Tree
+//!
+//! - `BIT2D`: H×W の格子に対する一点加算・長方形和を$O(log(H) log(W))$で行う
+//! - `CompressedBIT2D`: 加算する点をあらかじめ与えることで、座標が大きい疎な点集合に対して
+//!   一点加算・長方形和を$O(log^2(N))$、メモリ$O(N log(N))$で行う（N は点の数）
+
+use super::algebra::Group;
+use super::bit::range_to_pair;
+use std::ops::{Bound, RangeBounds};
+
+pub struct BIT2D<G: Group> {
+    h: usize,
+    w: usize,
+    bit: Vec<Vec<G::S>>,
+}
+
+impl<G: Group> BIT2D<G> {
+    pub fn new(h: usize, w: usize) -> Self {
+        Self {
+            h,
+            w,
+            bit: vec![vec![G::identity(); w + 1]; h + 1],
+        }
+    }
+
+    /// $a_{i,j}$ に x を加算する
+    pub fn add(&mut self, i: usize, j: usize, x: G::S) {
+        assert!(i < self.h && j < self.w);
+        let mut p = i + 1;
+        while p <= self.h {
+            let mut q = j + 1;
+            while q <= self.w {
+                self.bit[p][q] = G::op(&self.bit[p][q], &x);
+                q += q & q.wrapping_neg();
+            }
+            p += p & p.wrapping_neg();
+        }
+    }
+
+    /// $[0, h) \times [0, w)$ の総和を求める
+    pub fn sum(&self, h: usize, w: usize) -> G::S {
+        assert!(h <= self.h && w <= self.w);
+        let mut s = G::identity();
+        let mut p = h;
+        while p > 0 {
+            let mut q = w;
+            while q > 0 {
+                s = G::op(&s, &self.bit[p][q]);
+                q -= q & q.wrapping_neg();
+            }
+            p -= p & p.wrapping_neg();
+        }
+        s
+    }
+
+    /// 長方形 `rows` × `cols` の総和を求める
+    pub fn sum_rect<R1, R2>(&self, rows: R1, cols: R2) -> G::S
+    where
+        R1: RangeBounds<usize>,
+        R2: RangeBounds<usize>,
+    {
+        let (u, d) = range_to_pair(rows, self.h);
+        let (l, r) = range_to_pair(cols, self.w);
+        assert!(u <= d && d <= self.h && l <= r && r <= self.w);
+        let s = G::op(&self.sum(d, r), &self.sum(u, l));
+        let t = G::op(&self.sum(u, r), &self.sum(d, l));
+        G::op(&s, &G::inverse(&t))
+    }
+}
+
+/// 座標圧縮付き 2次元 BIT
+///
+/// `add` できるのは `new` に渡した点のみ
+pub struct CompressedBIT2D<K: Ord + Clone, G: Group> {
+    xs: Vec<K>,
+    ys: Vec<Vec<K>>,
+    bit: Vec<Vec<G::S>>,
+}
+
+impl<K: Ord + Clone, G: Group> CompressedBIT2D<K, G> {
+    pub fn new(points: &[(K, K)]) -> Self {
+        let mut xs: Vec<K> = points.iter().map(|(x, _)| x.clone()).collect();
+        xs.sort();
+        xs.dedup();
+        let n = xs.len();
+        let mut ys = vec![Vec::new(); n + 1];
+        for (x, y) in points {
+            let mut i = xs.partition_point(|v| v < x) + 1;
+            while i <= n {
+                ys[i].push(y.clone());
+                i += i & i.wrapping_neg();
+            }
+        }
+        for y in ys.iter_mut() {
+            y.sort();
+            y.dedup();
+        }
+        let bit = ys
+            .iter()
+            .map(|y| vec![G::identity(); y.len() + 1])
+            .collect();
+        Self { xs, ys, bit }
+    }
+
+    /// 点 (x, y) に w を加算する
+    pub fn add(&mut self, x: &K, y: &K, w: G::S) {
+        let i = self.xs.partition_point(|v| v < x);
+        assert!(
+            i < self.xs.len() && &self.xs[i] == x,
+            "point was not registered"
+        );
+        let mut i = i + 1;
+        while i <= self.xs.len() {
+            let ys = &self.ys[i];
+            let j = ys.partition_point(|v| v < y);
+            assert!(j < ys.len() && &ys[j] == y, "point was not registered");
+            let bit = &mut self.bit[i];
+            let mut j = j + 1;
+            while j < bit.len() {
+                bit[j] = G::op(&bit[j], &w);
+                j += j & j.wrapping_neg();
+            }
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// 長方形 `xr` × `yr` の総和を求める
+    pub fn sum_rect<R1, R2>(&self, xr: R1, yr: R2) -> G::S
+    where
+        R1: RangeBounds<K>,
+        R2: RangeBounds<K>,
+    {
+        let (l, r) = bounds_to_indices(&self.xs, &xr);
+        if l >= r {
+            return G::identity();
+        }
+        G::op(&self.prefix(r, &yr), &G::inverse(&self.prefix(l, &yr)))
+    }
+
+    /// x 座標が小さい方から i 個の点のうち、y 座標が `yr` に含まれるものの総和
+    fn prefix<R: RangeBounds<K>>(&self, i: usize, yr: &R) -> G::S {
+        let mut s = G::identity();
+        let mut i = i;
+        while i > 0 {
+            let (l, r) = bounds_to_indices(&self.ys[i], yr);
+            if l < r {
+                let bit = &self.bit[i];
+                s = G::op(&s, &Self::inner_sum(bit, r));
+                s = G::op(&s, &G::inverse(&Self::inner_sum(bit, l)));
+            }
+            i -= i & i.wrapping_neg();
+        }
+        s
+    }
+
+    fn inner_sum(bit: &[G::S], r: usize) -> G::S {
+        let mut s = G::identity();
+        let mut j = r;
+        while j > 0 {
+            s = G::op(&s, &bit[j]);
+            j -= j & j.wrapping_neg();
+        }
+        s
+    }
+}
+
+/// ソート済みの列 `sorted` のうち `range` に含まれる要素の添字の区間を求める
+fn bounds_to_indices<K: Ord, R: RangeBounds<K>>(sorted: &[K], range: &R) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(x) => sorted.partition_point(|v| v < x),
+        Bound::Excluded(x) => sorted.partition_point(|v| v <= x),
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(x) => sorted.partition_point(|v| v <= x),
+        Bound::Excluded(x) => sorted.partition_point(|v| v < x),
+        Bound::Unbounded => sorted.len(),
+    };
+    (l, r)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Additive;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn bit_2d_test() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let (h, w) = (7, 9);
+        let mut bit = BIT2D::<Additive<i64>>::new(h, w);
+        let mut naive = vec![vec![0i64; w]; h];
+        for _ in 0..500 {
+            let (i, j) = (rng.random_range(0..h), rng.random_range(0..w));
+            let x = rng.random_range(-10..=10);
+            bit.add(i, j, x);
+            naive[i][j] += x;
+            let u = rng.random_range(0..=h);
+            let d = rng.random_range(u..=h);
+            let l = rng.random_range(0..=w);
+            let r = rng.random_range(l..=w);
+            let expected: i64 = naive[u..d]
+                .iter()
+                .map(|row| row[l..r].iter().sum::<i64>())
+                .sum();
+            assert_eq!(bit.sum_rect(u..d, l..r), expected);
+        }
+        assert_eq!(bit.sum(h, w), bit.sum_rect(.., ..));
+    }
+
+    #[test]
+    fn compressed_bit_2d_test() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let points: Vec<(i64, i64)> = (0..50)
+            .map(|_| {
+                (
+                    rng.random_range(-1_000_000_000..=1_000_000_000),
+                    rng.random_range(-1_000_000_000..=1_000_000_000),
+                )
+            })
+            .collect();
+        let mut bit = CompressedBIT2D::<i64, Additive<i64>>::new(&points);
+        let mut weight = vec![0i64; points.len()];
+        for _ in 0..300 {
+            let k = rng.random_range(0..points.len());
+            let x = rng.random_range(-10..=10);
+            bit.add(&points[k].0, &points[k].1, x);
+            weight[k] += x;
+
+            let mut xr = [
+                points[rng.random_range(0..points.len())].0,
+                rng.random_range(-1_000_000_000..=1_000_000_000),
+            ];
+            let mut yr = [
+                points[rng.random_range(0..points.len())].1,
+                rng.random_range(-1_000_000_000..=1_000_000_000),
+            ];
+            xr.sort();
+            yr.sort();
+            let expected: i64 = points
+                .iter()
+                .zip(weight.iter())
+                .filter(|((x, y), _)| xr[0] <= *x && *x < xr[1] && yr[0] <= *y && *y <= yr[1])
+                .map(|(_, w)| w)
+                .sum();
+            assert_eq!(bit.sum_rect(xr[0]..xr[1], yr[0]..=yr[1]), expected);
+        }
+        assert_eq!(bit.sum_rect(.., ..), weight.iter().sum::<i64>());
+    }
+}
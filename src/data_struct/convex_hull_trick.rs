@@ -0,0 +1,409 @@
+//! Convex Hull Trick
+//!
+//! 直線 $y = ax + b$ の集合に対して、x での最小値を求める
+//! - `MonotoneCHT`: 傾きが単調非増加な順に直線を追加する。クエリは二分探索で$O(log(N))$、
+//!   x が単調非減少なら `query_monotone` で償却$O(1)$（`query` と混ぜて使ってよい）
+//! - `LineContainer`: 任意の順に直線を追加できる。追加・クエリともに$O(log(N))$
+//!
+//! 最大値が欲しい場合は a, b の符号を反転して追加し、結果の符号を反転すること。
+//!
+//! 直線が不要かどうかは交点の x 座標（整数なら切り捨て）の比較で判定するので、
+//! 係数同士の積を取らずに済む。a, b の絶対値は `LineNum::MAX_COEF`（整数なら型の最大値の半分）以下であること。
+//! このとき切片・傾きの差や符号の反転はオーバーフローせず、オーバーフローするのは $ax + b$ 自体が型に収まらない場合のみ
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// 直線の係数と座標に使う数
+pub trait LineNum:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+{
+    const INF: Self;
+    const NEG_INF: Self;
+    /// 直線の係数の絶対値の上限
+    const MAX_COEF: Self;
+    /// 整数なら $\lfloor a / b \rfloor$、浮動小数点数なら $a / b$
+    fn floor_div(a: Self, b: Self) -> Self;
+}
+
+macro_rules! impl_line_num_int {
+    ($($t: ty),*) => {$(
+        impl LineNum for $t {
+            const INF: Self = <$t>::MAX;
+            const NEG_INF: Self = <$t>::MIN;
+            const MAX_COEF: Self = <$t>::MAX / 2;
+            fn floor_div(a: Self, b: Self) -> Self {
+                a / b - if (a ^ b) < 0 && a % b != 0 { 1 } else { 0 }
+            }
+        }
+    )*};
+}
+impl_line_num_int!(i32, i64, i128, isize);
+
+macro_rules! impl_line_num_float {
+    ($($t: ty),*) => {$(
+        impl LineNum for $t {
+            const INF: Self = <$t>::INFINITY;
+            const NEG_INF: Self = <$t>::NEG_INFINITY;
+            const MAX_COEF: Self = <$t>::MAX;
+            fn floor_div(a: Self, b: Self) -> Self {
+                a / b
+            }
+        }
+    )*};
+}
+impl_line_num_float!(f32, f64);
+
+/// 直線 l1 が直線 l2 以下となる最大の x（l1 の傾き > l2 の傾き）
+fn last_better<T: LineNum>(l1: (T, T), l2: (T, T)) -> T {
+    T::floor_div(l2.1 - l1.1, l1.0 - l2.0)
+}
+
+pub(crate) fn check_coef<T: LineNum>(a: T, b: T) {
+    let ok = |v: T| -T::MAX_COEF <= v && v <= T::MAX_COEF;
+    assert!(ok(a) && ok(b), "coefficients must be within ±MAX_COEF");
+}
+
+fn eval<T: LineNum>(l: (T, T), x: T) -> T {
+    l.0 * x + l.1
+}
+
+/// 傾きが単調非増加な順に直線を追加する CHT（最小値）
+#[derive(Clone, Debug)]
+pub struct MonotoneCHT<T: LineNum> {
+    lines: Vec<(T, T)>,
+    // `query_monotone` で次に見る直線。これより前の直線は以降の x で最小にならない
+    head: usize,
+}
+
+impl<T: LineNum> Default for MonotoneCHT<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LineNum> MonotoneCHT<T> {
+    pub fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            head: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// 直線 $y = ax + b$ を追加する。a はこれまでに追加した傾き以下であること
+    pub fn add_line(&mut self, a: T, b: T) {
+        check_coef(a, b);
+        if let Some(&(la, lb)) = self.lines.last() {
+            assert!(a <= la, "slopes must be non-increasing");
+            if a == la {
+                if lb <= b {
+                    return;
+                }
+                self.lines.pop();
+            }
+        }
+        while self.lines.len() >= 2 {
+            let n = self.lines.len();
+            let (l1, l2) = (self.lines[n - 2], self.lines[n - 1]);
+            if last_better(l1, l2) >= last_better(l2, (a, b)) {
+                self.lines.pop();
+            } else {
+                break;
+            }
+        }
+        self.lines.push((a, b));
+        self.head = self.head.min(self.lines.len() - 1);
+    }
+
+    /// x での最小値
+    pub fn query(&self, x: T) -> Option<T> {
+        if self.lines.is_empty() {
+            return None;
+        }
+        // lines[i] が lines[i + 1] 以下となる最初の i
+        let (mut ok, mut ng) = (self.lines.len() - 1, 0);
+        if ok == 0 || x <= last_better(self.lines[0], self.lines[1]) {
+            return Some(eval(self.lines[0], x));
+        }
+        while ok - ng > 1 {
+            let mid = (ok + ng) / 2;
+            if x <= last_better(self.lines[mid], self.lines[mid + 1]) {
+                ok = mid;
+            } else {
+                ng = mid;
+            }
+        }
+        Some(eval(self.lines[ok], x))
+    }
+
+    /// x での最小値。x はこれまでの `query_monotone` の引数以上であること
+    pub fn query_monotone(&mut self, x: T) -> Option<T> {
+        while self.head + 1 < self.lines.len()
+            && eval(self.lines[self.head], x) >= eval(self.lines[self.head + 1], x)
+        {
+            self.head += 1;
+        }
+        self.lines.get(self.head).map(|&l| eval(l, x))
+    }
+}
+
+/// 全順序として扱う `PartialOrd`（NaN は含まないこと）
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Total<T>(T);
+
+impl<T: PartialEq> Eq for Total<T> {}
+
+impl<T: PartialOrd> PartialOrd for Total<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: PartialOrd> Ord for Total<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.partial_cmp(&other.0).expect("NaN is not allowed")
+    }
+}
+
+/// 任意の順に直線を追加できる CHT（最小値）
+///
+/// 内部では符号を反転した直線の上側を管理する
+#[derive(Clone, Debug)]
+pub struct LineContainer<T: LineNum> {
+    // 傾き -> (切片, この直線が最大となる最後の x)
+    lines: BTreeMap<Total<T>, (T, T)>,
+    // (最後の x, 傾き)
+    ends: BTreeSet<(Total<T>, Total<T>)>,
+}
+
+impl<T: LineNum> Default for LineContainer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LineNum> LineContainer<T> {
+    pub fn new() -> Self {
+        Self {
+            lines: BTreeMap::new(),
+            ends: BTreeSet::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// 直線 $y = ax + b$ を追加する
+    pub fn add_line(&mut self, a: T, b: T) {
+        check_coef(a, b);
+        let (k, m) = (Total(-a), -b);
+        if let Some(&(m0, _)) = self.lines.get(&k) {
+            if m0 >= m {
+                return;
+            }
+            self.remove(k);
+        }
+        self.lines.insert(k, (m, T::INF));
+        self.ends.insert((Total(T::INF), k));
+
+        while self.isect(k, self.succ(k)) {
+            let z = self.succ(k).unwrap();
+            self.remove(z);
+        }
+        let mut x = k;
+        if let Some(p) = self.pred(k) {
+            x = p;
+            if self.isect(x, Some(k)) {
+                self.remove(k);
+                self.isect(x, self.succ(x));
+            }
+        }
+        while let Some(p) = self.pred(x) {
+            let y = x;
+            x = p;
+            if self.lines[&x].1 >= self.lines[&y].1 {
+                self.remove(y);
+                self.isect(x, self.succ(x));
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// x での最小値
+    pub fn query(&self, x: T) -> Option<T> {
+        let &(_, k) = self.ends.range((Total(x), Total(T::NEG_INF))..).next()?;
+        let m = self.lines[&k].0;
+        Some(-(k.0 * x + m))
+    }
+
+    /// x の直線が最大となる最後の x を計算し直し、次の直線 y が不要になったかを返す
+    fn isect(&mut self, x: Total<T>, y: Option<Total<T>>) -> bool {
+        let (m, old) = self.lines[&x];
+        let (p, res) = match y {
+            None => (T::INF, false),
+            Some(y) => {
+                let (my, py) = self.lines[&y];
+                let p = T::floor_div(my - m, x.0 - y.0);
+                (p, p >= py)
+            }
+        };
+        self.ends.remove(&(Total(old), x));
+        self.ends.insert((Total(p), x));
+        self.lines.insert(x, (m, p));
+        res
+    }
+
+    fn remove(&mut self, k: Total<T>) {
+        let (_, p) = self.lines.remove(&k).unwrap();
+        self.ends.remove(&(Total(p), k));
+    }
+
+    fn succ(&self, k: Total<T>) -> Option<Total<T>> {
+        self.lines
+            .range((std::ops::Bound::Excluded(k), std::ops::Bound::Unbounded))
+            .next()
+            .map(|(&k, _)| k)
+    }
+
+    fn pred(&self, k: Total<T>) -> Option<Total<T>> {
+        self.lines.range(..k).next_back().map(|(&k, _)| k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn naive_min(lines: &[(i64, i64)], x: i64) -> Option<i64> {
+        lines.iter().map(|&(a, b)| a * x + b).min()
+    }
+
+    #[test]
+    fn floor_div_test() {
+        assert_eq!(i64::floor_div(7, 2), 3);
+        assert_eq!(i64::floor_div(-7, 2), -4);
+        assert_eq!(i64::floor_div(7, -2), -4);
+        assert_eq!(i64::floor_div(-7, -2), 3);
+        assert_eq!(i64::floor_div(-6, 2), -3);
+    }
+
+    #[test]
+    fn monotone_cht_test() {
+        let mut rng = StdRng::seed_from_u64(21);
+        for _ in 0..50 {
+            let mut lines: Vec<(i64, i64)> = (0..30)
+                .map(|_| (rng.random_range(-20..=20), rng.random_range(-100..=100)))
+                .collect();
+            lines.sort_by_key(|l| std::cmp::Reverse(l.0));
+            let mut cht = MonotoneCHT::new();
+            let mut added = Vec::new();
+            let mut x_mono = -60;
+            for &(a, b) in lines.iter() {
+                cht.add_line(a, b);
+                added.push((a, b));
+                for x in -30..=30 {
+                    assert_eq!(cht.query(x), naive_min(&added, x));
+                }
+                // 直線の追加と `query_monotone` を交互に行う
+                x_mono += rng.random_range(0..=2);
+                assert_eq!(cht.query_monotone(x_mono), naive_min(&added, x_mono));
+            }
+            for x in x_mono..=x_mono + 60 {
+                assert_eq!(cht.query_monotone(x), naive_min(&added, x));
+                // 大きい x で `query_monotone` した後も `query` は正しい
+                assert_eq!(cht.query(-x), naive_min(&added, -x));
+            }
+        }
+        assert_eq!(MonotoneCHT::<i64>::new().query(0), None);
+    }
+
+    #[test]
+    fn line_container_test() {
+        let mut rng = StdRng::seed_from_u64(22);
+        for _ in 0..50 {
+            let mut cht = LineContainer::new();
+            let mut added = Vec::new();
+            for _ in 0..30 {
+                let (a, b) = (rng.random_range(-20..=20), rng.random_range(-100..=100));
+                cht.add_line(a, b);
+                added.push((a, b));
+                for x in -30..=30 {
+                    assert_eq!(cht.query(x), naive_min(&added, x));
+                }
+            }
+        }
+        assert_eq!(LineContainer::<i64>::new().query(0), None);
+    }
+
+    #[test]
+    fn line_container_large_test() {
+        // 交点を積で比較すると i64 に収まらない大きさの係数
+        let big = 1_000_000_000_000_000_000;
+        let mut cht = LineContainer::<i64>::new();
+        cht.add_line(1_000_000_000, -big);
+        cht.add_line(-1_000_000_000, big);
+        cht.add_line(0, 0);
+        assert_eq!(cht.query(0), Some(-big));
+        assert_eq!(cht.query(1), Some(1_000_000_000 - big));
+        assert_eq!(cht.query(-1), Some(-1_000_000_000 - big));
+        let mut cht = MonotoneCHT::<i64>::new();
+        cht.add_line(1_000_000_000, -big);
+        cht.add_line(0, 0);
+        cht.add_line(-1_000_000_000, big);
+        assert_eq!(cht.query(0), Some(-big));
+        assert_eq!(cht.query(3), Some(3_000_000_000 - big));
+
+        let mut cht = LineContainer::<f64>::new();
+        cht.add_line(0.5, 1.0);
+        cht.add_line(-0.5, 2.0);
+        assert_eq!(cht.query(0.0), Some(1.0));
+        assert_eq!(cht.query(2.0), Some(1.0));
+        assert_eq!(cht.query(4.0), Some(0.0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn line_container_min_coef_test() {
+        LineContainer::<i64>::new().add_line(0, i64::MIN);
+    }
+
+    #[test]
+    fn max_coef_test() {
+        // 係数が上限ちょうどでも交点の計算はオーバーフローしない
+        let c = i64::MAX_COEF;
+        let mut cht = LineContainer::<i64>::new();
+        cht.add_line(c, -c);
+        cht.add_line(-c, c);
+        cht.add_line(0, c);
+        assert_eq!(cht.query(0), Some(-c));
+        assert_eq!(cht.query(1), Some(0));
+        let mut cht = MonotoneCHT::<i64>::new();
+        cht.add_line(c, -c);
+        cht.add_line(0, c);
+        cht.add_line(-c, c);
+        assert_eq!(cht.query(0), Some(-c));
+        assert_eq!(cht.query(1), Some(0));
+    }
+}
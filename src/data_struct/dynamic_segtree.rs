@@ -0,0 +1,496 @@
+//! 動的セグメント木
+//!
+//! 添字の範囲 $[0, n)$ が $10^{18}$ 程度と大きくても、触れた部分のノードだけを確保する。
+//! 座標圧縮ができないオンラインのクエリ向け
+//! - `DynamicSegtree`: 一点更新・区間積
+//! - `DynamicLazySegtree`: 区間作用・区間積
+//! - 計算量はいずれも$O(log(N))$で、1回の操作で確保するノードは$O(log(N))$個
+//!
+//! 初期値は単位元のほか、`with_default` で全要素を同じ値にしておける
+//! （総和を扱う場合は `(0, 1)` とすること。`SumWithLen` を参照）。
+//!
+//! 区間の指定は `Segtree::prod` と同じく `RangeBounds` で行う
+
+use super::algebra::{Action, Monoid};
+use super::bit::range_to_pair;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+// 子の添字 0 は「まだ確保していない」を表す（0 は根なので子にはならない）
+#[derive(Clone)]
+struct Node<S> {
+    left: usize,
+    right: usize,
+    val: S,
+}
+
+/// 長さ n の列を 2 冪の完全二分木で表したときの、幅 $2^k$ の区間の初期値
+fn default_pows<M: Monoid>(n: usize, x: M::S) -> (usize, Vec<M::S>) {
+    let log = n.next_power_of_two().trailing_zeros() as usize;
+    let mut pows = vec![x];
+    for k in 0..log {
+        let x = M::op(&pows[k], &pows[k]);
+        pows.push(x);
+    }
+    (log, pows)
+}
+
+pub struct DynamicSegtree<M: Monoid> {
+    n: usize,
+    log: usize,
+    pows: Vec<M::S>,
+    nodes: Vec<Node<M::S>>,
+}
+
+impl<M: Monoid> Clone for DynamicSegtree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            log: self.log,
+            pows: self.pows.clone(),
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> DynamicSegtree<M> {
+    /// 全要素が単位元である長さ n の列
+    pub fn new(n: usize) -> Self {
+        Self::with_default(n, M::identity())
+    }
+
+    /// 全要素が x である長さ n の列
+    pub fn with_default(n: usize, x: M::S) -> Self {
+        let (log, pows) = default_pows::<M>(n, x);
+        let root = Node {
+            left: 0,
+            right: 0,
+            val: pows[log].clone(),
+        };
+        Self {
+            n,
+            log,
+            pows,
+            nodes: vec![root],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_p$ を x に更新する
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let mut path = Vec::with_capacity(self.log);
+        let mut k = 0;
+        for d in (0..self.log).rev() {
+            path.push((k, d));
+            k = self.child(k, p >> d & 1, d);
+        }
+        self.nodes[k].val = x;
+        for (k, d) in path.into_iter().rev() {
+            let (l, r) = (self.nodes[k].left, self.nodes[k].right);
+            self.nodes[k].val = M::op(&self.val(l, d), &self.val(r, d));
+        }
+    }
+
+    pub fn get(&self, p: usize) -> M::S {
+        assert!(p < self.n);
+        let mut k = 0;
+        for d in (0..self.log).rev() {
+            k = if p >> d & 1 == 0 {
+                self.nodes[k].left
+            } else {
+                self.nodes[k].right
+            };
+            if k == 0 {
+                return self.pows[0].clone();
+            }
+        }
+        self.nodes[k].val.clone()
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        self.prod_rec(Some(0), self.log, 0, l, r)
+    }
+
+    /// 確保したノードの個数
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// ノード k の子（幅 $2^d$）を、なければ確保して返す
+    fn child(&mut self, k: usize, bit: usize, d: usize) -> usize {
+        let c = if bit == 0 {
+            self.nodes[k].left
+        } else {
+            self.nodes[k].right
+        };
+        if c != 0 {
+            return c;
+        }
+        self.nodes.push(Node {
+            left: 0,
+            right: 0,
+            val: self.pows[d].clone(),
+        });
+        let c = self.nodes.len() - 1;
+        if bit == 0 {
+            self.nodes[k].left = c;
+        } else {
+            self.nodes[k].right = c;
+        }
+        c
+    }
+
+    fn val(&self, k: usize, d: usize) -> M::S {
+        if k == 0 {
+            self.pows[d].clone()
+        } else {
+            self.nodes[k].val.clone()
+        }
+    }
+
+    fn prod_rec(&self, k: Option<usize>, d: usize, lo: usize, l: usize, r: usize) -> M::S {
+        let hi = lo + (1 << d);
+        if r <= lo || hi <= l {
+            return M::identity();
+        }
+        if l <= lo && hi <= r {
+            return match k {
+                Some(k) => self.nodes[k].val.clone(),
+                None => self.pows[d].clone(),
+            };
+        }
+        let (left, right) = match k {
+            Some(k) => {
+                let node = &self.nodes[k];
+                (
+                    Some(node.left).filter(|&c| c != 0),
+                    Some(node.right).filter(|&c| c != 0),
+                )
+            }
+            None => (None, None),
+        };
+        let mid = lo + (1 << (d - 1));
+        M::op(
+            &self.prod_rec(left, d - 1, lo, l, r),
+            &self.prod_rec(right, d - 1, mid, l, r),
+        )
+    }
+}
+
+#[derive(Clone)]
+struct LazyNode<S, F> {
+    left: usize,
+    right: usize,
+    val: S,
+    lz: F,
+}
+
+pub struct DynamicLazySegtree<M: Monoid, F: Action<M>> {
+    n: usize,
+    log: usize,
+    pows: Vec<M::S>,
+    nodes: Vec<LazyNode<M::S, F::S>>,
+    _phantom: PhantomData<(M, F)>,
+}
+
+impl<M: Monoid, F: Action<M>> Clone for DynamicLazySegtree<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            log: self.log,
+            pows: self.pows.clone(),
+            nodes: self.nodes.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: Monoid, F: Action<M>> DynamicLazySegtree<M, F> {
+    /// 全要素が単位元である長さ n の列
+    pub fn new(n: usize) -> Self {
+        Self::with_default(n, M::identity())
+    }
+
+    /// 全要素が x である長さ n の列
+    pub fn with_default(n: usize, x: M::S) -> Self {
+        let (log, pows) = default_pows::<M>(n, x);
+        let root = LazyNode {
+            left: 0,
+            right: 0,
+            val: pows[log].clone(),
+            lz: F::identity(),
+        };
+        Self {
+            n,
+            log,
+            pows,
+            nodes: vec![root],
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_p$ を x に更新する
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let mut path = Vec::with_capacity(self.log);
+        let mut k = 0;
+        for d in (0..self.log).rev() {
+            path.push(k);
+            self.push(k, d);
+            k = if p >> d & 1 == 0 {
+                self.nodes[k].left
+            } else {
+                self.nodes[k].right
+            };
+        }
+        self.nodes[k].val = x;
+        for k in path.into_iter().rev() {
+            self.update(k);
+        }
+    }
+
+    pub fn get(&mut self, p: usize) -> M::S {
+        assert!(p < self.n);
+        self.prod(p..=p)
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&mut self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        self.prod_rec(0, self.log, 0, l, r)
+    }
+
+    /// 区間 `range` の各要素に f を作用させる
+    pub fn apply<R: RangeBounds<usize>>(&mut self, range: R, f: F::S) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return;
+        }
+        self.apply_rec(0, self.log, 0, l, r, &f);
+    }
+
+    /// 確保したノードの個数
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn prod_rec(&mut self, k: usize, d: usize, lo: usize, l: usize, r: usize) -> M::S {
+        let hi = lo + (1 << d);
+        if r <= lo || hi <= l {
+            return M::identity();
+        }
+        if l <= lo && hi <= r {
+            return self.nodes[k].val.clone();
+        }
+        self.push(k, d - 1);
+        let mid = lo + (1 << (d - 1));
+        let (left, right) = (self.nodes[k].left, self.nodes[k].right);
+        M::op(
+            &self.prod_rec(left, d - 1, lo, l, r),
+            &self.prod_rec(right, d - 1, mid, l, r),
+        )
+    }
+
+    fn apply_rec(&mut self, k: usize, d: usize, lo: usize, l: usize, r: usize, f: &F::S) {
+        let hi = lo + (1 << d);
+        if r <= lo || hi <= l {
+            return;
+        }
+        if l <= lo && hi <= r {
+            self.all_apply(k, f);
+            return;
+        }
+        self.push(k, d - 1);
+        let mid = lo + (1 << (d - 1));
+        let (left, right) = (self.nodes[k].left, self.nodes[k].right);
+        self.apply_rec(left, d - 1, lo, l, r, f);
+        self.apply_rec(right, d - 1, mid, l, r, f);
+        self.update(k);
+    }
+
+    fn update(&mut self, k: usize) {
+        let (l, r) = (self.nodes[k].left, self.nodes[k].right);
+        self.nodes[k].val = M::op(&self.nodes[l].val, &self.nodes[r].val);
+    }
+
+    fn all_apply(&mut self, k: usize, f: &F::S) {
+        let node = &mut self.nodes[k];
+        node.val = F::act(f, &node.val);
+        node.lz = F::op(&node.lz, f);
+    }
+
+    /// ノード k（幅 $2^{d+1}$）の子を確保し、遅延していた作用を伝える
+    fn push(&mut self, k: usize, d: usize) {
+        for bit in 0..2 {
+            let c = if bit == 0 {
+                self.nodes[k].left
+            } else {
+                self.nodes[k].right
+            };
+            if c == 0 {
+                self.nodes.push(LazyNode {
+                    left: 0,
+                    right: 0,
+                    val: self.pows[d].clone(),
+                    lz: F::identity(),
+                });
+                let c = self.nodes.len() - 1;
+                if bit == 0 {
+                    self.nodes[k].left = c;
+                } else {
+                    self.nodes[k].right = c;
+                }
+            }
+        }
+        let f = std::mem::replace(&mut self.nodes[k].lz, F::identity());
+        let (l, r) = (self.nodes[k].left, self.nodes[k].right);
+        self.all_apply(l, &f);
+        self.all_apply(r, &f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::{Additive, Affine, Min, SumWithLen};
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    type Mint = ModInt998244353;
+
+    #[test]
+    fn dynamic_segtree_test() {
+        let mut rng = StdRng::seed_from_u64(26);
+        for n in 0..20 {
+            let mut seg = DynamicSegtree::<Affine<Mint>>::new(n);
+            let mut naive = vec![Affine::<Mint>::identity(); n];
+            for _ in 0..100 {
+                if n > 0 && rng.random_bool(0.5) {
+                    let p = rng.random_range(0..n);
+                    let x = (
+                        Mint::new(rng.random_range(0..10)),
+                        Mint::new(rng.random_range(0..10)),
+                    );
+                    seg.set(p, x);
+                    naive[p] = x;
+                }
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let expected = naive[l..r]
+                    .iter()
+                    .fold(Affine::<Mint>::identity(), |f, g| Affine::<Mint>::op(&f, g));
+                assert_eq!(seg.prod(l..r), expected);
+                for (i, x) in naive.iter().enumerate() {
+                    assert_eq!(seg.get(i), *x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dynamic_segtree_huge_test() {
+        let mut rng = StdRng::seed_from_u64(27);
+        let n = 1_000_000_000_000_000_000;
+        // 全要素が 1 の列に対する区間和
+        let mut seg = DynamicSegtree::<Additive<u64>>::with_default(n, 1);
+        let mut naive = BTreeMap::new();
+        for _ in 0..500 {
+            let p = rng.random_range(0..n);
+            let x = rng.random_range(0..100);
+            seg.set(p, x);
+            naive.insert(p, x);
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let expected = naive
+                .range(l..r)
+                .fold((r - l) as u64, |s, (_, &x)| s + x - 1);
+            assert_eq!(seg.prod(l..r), expected);
+            assert_eq!(seg.get(p), x);
+        }
+        assert_eq!(
+            seg.prod(..),
+            n as u64 + naive.values().sum::<u64>() - naive.len() as u64
+        );
+        assert!(seg.node_count() <= 500 * 61);
+    }
+
+    #[test]
+    fn dynamic_lazy_segtree_test() {
+        let mut rng = StdRng::seed_from_u64(28);
+        for n in 1..20 {
+            let mut seg = DynamicLazySegtree::<SumWithLen<Mint>, Affine<Mint>>::with_default(
+                n,
+                (Mint::new(0), Mint::new(1)),
+            );
+            let mut naive = vec![Mint::new(0); n];
+            for _ in 0..100 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                match rng.random_range(0..3) {
+                    0 => {
+                        let a = Mint::new(rng.random_range(0..10));
+                        let b = Mint::new(rng.random_range(0..10));
+                        seg.apply(l..r, (a, b));
+                        naive[l..r].iter_mut().for_each(|x| *x = a * *x + b);
+                    }
+                    1 if l < n => {
+                        let x = Mint::new(rng.random_range(0..10));
+                        seg.set(l, (x, Mint::new(1)));
+                        naive[l] = x;
+                    }
+                    _ => {
+                        let expected = naive[l..r].iter().fold(Mint::new(0), |s, &x| s + x);
+                        assert_eq!(seg.prod(l..r), (expected, Mint::new((r - l) as u64)));
+                    }
+                }
+            }
+            for (i, &x) in naive.iter().enumerate() {
+                assert_eq!(seg.get(i), (x, Mint::new(1)));
+            }
+        }
+    }
+
+    #[test]
+    fn dynamic_lazy_segtree_huge_test() {
+        let n = 1_000_000_000_000_000_000;
+        let mut seg = DynamicLazySegtree::<Min<i64>, Additive<i64>>::with_default(n, 0);
+        seg.apply(10..n - 10, 5);
+        seg.apply(500_000_000_000..600_000_000_000, -3);
+        assert_eq!(seg.prod(..), 0);
+        assert_eq!(seg.prod(10..n - 10), 2);
+        assert_eq!(seg.prod(10..500_000_000_000), 5);
+        assert_eq!(seg.get(599_999_999_999), 2);
+        assert_eq!(seg.get(n - 1), 0);
+        seg.set(n - 1, -7);
+        assert_eq!(seg.prod(n - 20..), -7);
+    }
+}
@@ -0,0 +1,412 @@
+//! Euler Tour Tree
+//!
+//! 頂点に 可換モノイド `M` の値を持つ森に対して、以下の様なことを償却$O(log(N))$で行う
+//! - 辺の追加（`link`）・削除（`cut`）と連結判定
+//! - 頂点の値の更新と、部分木・木全体の値の総積
+//!
+//! 木をオイラーツアー（頂点と、向きつきの辺を並べた列）として splay 木で管理する。
+//! 根の付け替えは列の巡回シフトになるので、値の順序は保たれない（可換であること）
+
+use super::algebra::Monoid;
+use std::collections::HashMap;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Clone)]
+struct Node<S> {
+    left: usize,
+    right: usize,
+    parent: usize,
+    val: S,
+    prod: S,
+}
+
+pub struct EulerTourTree<M: Monoid> {
+    n: usize,
+    // 0..n は頂点、それ以降は辺のノード
+    nodes: Vec<Node<M::S>>,
+    // 向きつきの辺 (u, v) -> ノード
+    edges: HashMap<(usize, usize), usize>,
+    // 再利用できる辺のノード
+    free: Vec<usize>,
+}
+
+impl<M: Monoid> Clone for EulerTourTree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            nodes: self.nodes.clone(),
+            edges: self.edges.clone(),
+            free: self.free.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> From<Vec<M::S>> for EulerTourTree<M> {
+    fn from(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let nodes = v
+            .into_iter()
+            .map(|x| Node {
+                left: NIL,
+                right: NIL,
+                parent: NIL,
+                val: x.clone(),
+                prod: x,
+            })
+            .collect();
+        Self {
+            n,
+            nodes,
+            edges: HashMap::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<M: Monoid> EulerTourTree<M> {
+    /// 頂点数 n、辺のない森
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 辺 (u, v) を追加する。u と v は別の木にあること
+    pub fn link(&mut self, u: usize, v: usize) {
+        assert!(!self.connected(u, v), "u and v are already connected");
+        let a = self.reroot(u);
+        let b = self.reroot(v);
+        let uv = self.new_edge(u, v);
+        let vu = self.new_edge(v, u);
+        let t = self.merge(a, uv);
+        let t = self.merge(t, b);
+        self.merge(t, vu);
+    }
+
+    /// 辺 (u, v) を削除する。辺 (u, v) が存在すること
+    pub fn cut(&mut self, u: usize, v: usize) {
+        let uv = self
+            .edges
+            .remove(&(u, v))
+            .expect("edge (u, v) does not exist");
+        let vu = self.edges.remove(&(v, u)).unwrap();
+        // u を根にすると列は A (u, v) B (v, u) C の形になり、B が v 側の木
+        self.reroot(u);
+        let (a, _) = self.split_before(uv);
+        self.split_after(uv);
+        let (_, c) = self.split_after(vu);
+        self.split_before(vu);
+        self.merge(a, c);
+        self.free.push(uv);
+        self.free.push(vu);
+    }
+
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        if u == v {
+            return true;
+        }
+        self.splay(u);
+        self.splay(v);
+        // 同じ splay 木にあれば、v を根にしたことで u は根でなくなる
+        self.nodes[u].parent != NIL
+    }
+
+    /// 頂点 v の値を x に更新する
+    pub fn set(&mut self, v: usize, x: M::S) {
+        self.splay(v);
+        self.nodes[v].val = x;
+        self.update(v);
+    }
+
+    pub fn get(&self, v: usize) -> M::S {
+        self.nodes[v].val.clone()
+    }
+
+    /// v を含む木全体の値の総積
+    pub fn tree_prod(&mut self, v: usize) -> M::S {
+        self.splay(v);
+        self.nodes[v].prod.clone()
+    }
+
+    /// 辺 (v, p) で切ったときの v 側の木（p を親としたときの v の部分木）の値の総積
+    pub fn subtree_prod(&mut self, v: usize, p: usize) -> M::S {
+        let pv = *self.edges.get(&(p, v)).expect("edge (v, p) does not exist");
+        let vp = self.edges[&(v, p)];
+        self.reroot(p);
+        let (a, _) = self.split_before(pv);
+        let (mid, c) = self.split_after(vp);
+        let res = self.nodes[mid].prod.clone();
+        let t = self.merge(a, mid);
+        self.merge(t, c);
+        res
+    }
+
+    fn new_edge(&mut self, u: usize, v: usize) -> usize {
+        let node = Node {
+            left: NIL,
+            right: NIL,
+            parent: NIL,
+            val: M::identity(),
+            prod: M::identity(),
+        };
+        let k = match self.free.pop() {
+            Some(k) => {
+                self.nodes[k] = node;
+                k
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.edges.insert((u, v), k);
+        k
+    }
+
+    /// v が列の先頭になるよう巡回シフトし、splay 木の根を返す
+    fn reroot(&mut self, v: usize) -> usize {
+        let (l, r) = self.split_before(v);
+        self.merge(r, l)
+    }
+
+    /// x の直前で分割する
+    fn split_before(&mut self, x: usize) -> (usize, usize) {
+        self.splay(x);
+        let l = self.nodes[x].left;
+        if l != NIL {
+            self.nodes[l].parent = NIL;
+            self.nodes[x].left = NIL;
+            self.update(x);
+        }
+        (l, x)
+    }
+
+    /// x の直後で分割する
+    fn split_after(&mut self, x: usize) -> (usize, usize) {
+        self.splay(x);
+        let r = self.nodes[x].right;
+        if r != NIL {
+            self.nodes[r].parent = NIL;
+            self.nodes[x].right = NIL;
+            self.update(x);
+        }
+        (x, r)
+    }
+
+    fn merge(&mut self, a: usize, b: usize) -> usize {
+        if a == NIL {
+            return b;
+        }
+        if b == NIL {
+            return a;
+        }
+        let mut x = a;
+        while self.nodes[x].right != NIL {
+            x = self.nodes[x].right;
+        }
+        self.splay(x);
+        self.nodes[x].right = b;
+        self.nodes[b].parent = x;
+        self.update(x);
+        x
+    }
+
+    fn update(&mut self, x: usize) {
+        let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+        let mut prod = self.nodes[x].val.clone();
+        if l != NIL {
+            prod = M::op(&self.nodes[l].prod, &prod);
+        }
+        if r != NIL {
+            prod = M::op(&prod, &self.nodes[r].prod);
+        }
+        self.nodes[x].prod = prod;
+    }
+
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].parent;
+        let g = self.nodes[p].parent;
+        if self.nodes[p].left == x {
+            let b = self.nodes[x].right;
+            self.nodes[p].left = b;
+            if b != NIL {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].right = p;
+        } else {
+            let b = self.nodes[x].left;
+            self.nodes[p].right = b;
+            if b != NIL {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].left = p;
+        }
+        if g != NIL {
+            if self.nodes[g].left == p {
+                self.nodes[g].left = x;
+            } else {
+                self.nodes[g].right = x;
+            }
+        }
+        self.nodes[p].parent = x;
+        self.nodes[x].parent = g;
+        self.update(p);
+        self.update(x);
+    }
+
+    fn splay(&mut self, x: usize) {
+        while self.nodes[x].parent != NIL {
+            let p = self.nodes[x].parent;
+            let g = self.nodes[p].parent;
+            if g != NIL {
+                let zig_zig = (self.nodes[g].left == p) == (self.nodes[p].left == x);
+                if zig_zig {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Additive;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    /// u から辿れる頂点（`banned` の辺は使わない）
+    fn reachable(adj: &[BTreeSet<usize>], u: usize, banned: (usize, usize)) -> Vec<bool> {
+        let mut seen = vec![false; adj.len()];
+        seen[u] = true;
+        let mut stack = vec![u];
+        while let Some(x) = stack.pop() {
+            for &y in adj[x].iter() {
+                if !seen[y] && (x, y) != banned && (y, x) != banned {
+                    seen[y] = true;
+                    stack.push(y);
+                }
+            }
+        }
+        seen
+    }
+
+    #[test]
+    fn euler_tour_tree_test() {
+        let mut rng = StdRng::seed_from_u64(38);
+        let n = 15;
+        let mut vals: Vec<i64> = (0..n).map(|_| rng.random_range(-100..=100)).collect();
+        let mut ett = EulerTourTree::<Additive<i64>>::from(vals.clone());
+        let mut adj = vec![BTreeSet::new(); n];
+        let sum = |vals: &[i64], seen: &[bool]| -> i64 {
+            (0..n).filter(|&i| seen[i]).map(|i| vals[i]).sum()
+        };
+        for _ in 0..3000 {
+            let u = rng.random_range(0..n);
+            let v = rng.random_range(0..n);
+            match rng.random_range(0..5) {
+                0 => {
+                    let connected = reachable(&adj, u, (NIL, NIL))[v];
+                    assert_eq!(ett.connected(u, v), connected);
+                    if !connected {
+                        ett.link(u, v);
+                        adj[u].insert(v);
+                        adj[v].insert(u);
+                    }
+                }
+                1 => {
+                    if let Some(&w) = adj[u].iter().next() {
+                        ett.cut(u, w);
+                        adj[u].remove(&w);
+                        adj[w].remove(&u);
+                    }
+                }
+                2 => {
+                    let x = rng.random_range(-100..=100);
+                    ett.set(u, x);
+                    vals[u] = x;
+                }
+                3 => {
+                    if let Some(&p) = adj[u].iter().next() {
+                        let seen = reachable(&adj, u, (u, p));
+                        assert_eq!(ett.subtree_prod(u, p), sum(&vals, &seen));
+                    }
+                }
+                _ => {
+                    let seen = reachable(&adj, u, (NIL, NIL));
+                    assert_eq!(ett.tree_prod(u), sum(&vals, &seen));
+                }
+            }
+            assert_eq!(ett.get(u), vals[u]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn euler_tour_tree_cut_non_edge_test() {
+        let mut ett = EulerTourTree::<Additive<i64>>::new(3);
+        ett.link(0, 1);
+        ett.link(1, 2);
+        // 0 と 2 は連結だが辺はない
+        ett.cut(0, 2);
+    }
+
+    #[test]
+    fn euler_tour_tree_relink_test() {
+        let mut ett = EulerTourTree::<Additive<i64>>::from(vec![1, 10, 100, 1000]);
+        ett.link(0, 1);
+        ett.link(1, 2);
+        ett.link(2, 3);
+        for _ in 0..3 {
+            // 同じ辺の削除と追加を繰り返す（辺のノードは再利用される）
+            ett.cut(1, 2);
+            assert!(!ett.connected(0, 3));
+            assert_eq!(ett.tree_prod(0), 11);
+            assert_eq!(ett.tree_prod(3), 1100);
+            ett.link(2, 1);
+            assert!(ett.connected(0, 3));
+            assert_eq!(ett.tree_prod(3), 1111);
+        }
+        // 別の辺でつなぎ直す
+        ett.cut(1, 2);
+        ett.link(3, 0);
+        assert!(ett.connected(1, 2));
+        assert_eq!(ett.subtree_prod(1, 0), 10);
+        assert_eq!(ett.subtree_prod(0, 3), 11);
+        assert_eq!(ett.subtree_prod(3, 0), 1100);
+    }
+
+    #[test]
+    fn euler_tour_tree_subtree_after_reroot_test() {
+        // 0 - 1 - 2, 1 - 3 - 4
+        let mut ett = EulerTourTree::<Additive<i64>>::from(vec![1, 10, 100, 1000, 10000, 100000]);
+        ett.link(0, 1);
+        ett.link(1, 2);
+        ett.link(1, 3);
+        ett.link(3, 4);
+        assert_eq!(ett.subtree_prod(3, 1), 11000);
+        assert_eq!(ett.subtree_prod(1, 3), 111);
+        assert_eq!(ett.subtree_prod(1, 0), 11110);
+        assert_eq!(ett.subtree_prod(0, 1), 1);
+        // link で内部の列の先頭が付け替わっても、部分木の値は変わらない
+        ett.link(5, 4);
+        assert_eq!(ett.subtree_prod(3, 1), 111000);
+        assert_eq!(ett.subtree_prod(1, 3), 111);
+        assert_eq!(ett.subtree_prod(4, 3), 110000);
+        assert_eq!(ett.subtree_prod(3, 4), 1111);
+        ett.set(2, 7);
+        assert_eq!(ett.subtree_prod(1, 0), 111017);
+        assert_eq!(ett.tree_prod(5), 111018);
+    }
+}
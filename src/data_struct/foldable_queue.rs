@@ -0,0 +1,296 @@
+//! Sliding Window Aggregation
+//!
+//! モノイド `M` の元を持つキュー・両端キューで、中身全体の総積を償却$O(1)$で求める
+//! - `FoldableQueue`: 末尾への追加と先頭からの削除
+//! - `FoldableDeque`: 両端での追加・削除
+//!
+//! どちらも2つのスタックで実装し、各スタックには要素と「その要素からスタックの底までの総積」を積む。
+//! 単に列の区間の最小値・最大値が欲しいだけなら `sliding_window_min` / `sliding_window_max` を使う
+
+use super::algebra::Monoid;
+use std::collections::VecDeque;
+
+pub struct FoldableQueue<M: Monoid> {
+    // 先頭側。末尾が先頭の要素で、総積はキューの順
+    front: Vec<(M::S, M::S)>,
+    // 末尾側。末尾が最後の要素
+    back: Vec<M::S>,
+    back_fold: M::S,
+}
+
+impl<M: Monoid> Clone for FoldableQueue<M> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+            back_fold: self.back_fold.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> Default for FoldableQueue<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Monoid> FoldableQueue<M> {
+    pub fn new() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+            back_fold: M::identity(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push(&mut self, x: M::S) {
+        self.back_fold = M::op(&self.back_fold, &x);
+        self.back.push(x);
+    }
+
+    pub fn pop(&mut self) -> Option<M::S> {
+        if self.front.is_empty() {
+            self.back_fold = M::identity();
+            while let Some(x) = self.back.pop() {
+                let fold = match self.front.last() {
+                    Some((_, f)) => M::op(&x, f),
+                    None => x.clone(),
+                };
+                self.front.push((x, fold));
+            }
+        }
+        self.front.pop().map(|(x, _)| x)
+    }
+
+    /// 中身全体の総積（先頭から順）
+    pub fn fold(&self) -> M::S {
+        match self.front.last() {
+            Some((_, f)) => M::op(f, &self.back_fold),
+            None => self.back_fold.clone(),
+        }
+    }
+}
+
+pub struct FoldableDeque<M: Monoid> {
+    // 先頭側。末尾が先頭の要素で、総積はキューの順
+    front: Vec<(M::S, M::S)>,
+    // 末尾側。末尾が最後の要素で、総積はキューの順
+    back: Vec<(M::S, M::S)>,
+}
+
+impl<M: Monoid> Clone for FoldableDeque<M> {
+    fn clone(&self) -> Self {
+        Self {
+            front: self.front.clone(),
+            back: self.back.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> Default for FoldableDeque<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Monoid> FoldableDeque<M> {
+    pub fn new() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn push_front(&mut self, x: M::S) {
+        Self::push_to_front(&mut self.front, x);
+    }
+
+    pub fn push_back(&mut self, x: M::S) {
+        Self::push_to_back(&mut self.back, x);
+    }
+
+    pub fn pop_front(&mut self) -> Option<M::S> {
+        if self.front.is_empty() {
+            // 末尾側の前半を先頭側へ移す
+            let items: Vec<M::S> = self.back.drain(..).map(|(x, _)| x).collect();
+            let mid = (items.len() + 1) / 2;
+            let mut items = items.into_iter();
+            let head: Vec<M::S> = items.by_ref().take(mid).collect();
+            for x in items {
+                Self::push_to_back(&mut self.back, x);
+            }
+            for x in head.into_iter().rev() {
+                Self::push_to_front(&mut self.front, x);
+            }
+        }
+        self.front.pop().map(|(x, _)| x)
+    }
+
+    pub fn pop_back(&mut self) -> Option<M::S> {
+        if self.back.is_empty() {
+            // 先頭側の後半を末尾側へ移す
+            let items: Vec<M::S> = self.front.drain(..).rev().map(|(x, _)| x).collect();
+            let mid = items.len() / 2;
+            let mut items = items.into_iter();
+            let head: Vec<M::S> = items.by_ref().take(mid).collect();
+            for x in items {
+                Self::push_to_back(&mut self.back, x);
+            }
+            for x in head.into_iter().rev() {
+                Self::push_to_front(&mut self.front, x);
+            }
+        }
+        self.back.pop().map(|(x, _)| x)
+    }
+
+    /// 中身全体の総積（先頭から順）
+    pub fn fold(&self) -> M::S {
+        match (self.front.last(), self.back.last()) {
+            (Some((_, f)), Some((_, g))) => M::op(f, g),
+            (Some((_, f)), None) => f.clone(),
+            (None, Some((_, g))) => g.clone(),
+            (None, None) => M::identity(),
+        }
+    }
+
+    fn push_to_front(front: &mut Vec<(M::S, M::S)>, x: M::S) {
+        let fold = match front.last() {
+            Some((_, f)) => M::op(&x, f),
+            None => x.clone(),
+        };
+        front.push((x, fold));
+    }
+
+    fn push_to_back(back: &mut Vec<(M::S, M::S)>, x: M::S) {
+        let fold = match back.last() {
+            Some((_, f)) => M::op(f, &x),
+            None => x.clone(),
+        };
+        back.push((x, fold));
+    }
+}
+
+/// 長さ k の各区間 `a[i..i + k]` の最小値（$O(N)$）
+pub fn sliding_window_min<T: PartialOrd + Clone>(a: &[T], k: usize) -> Vec<T> {
+    sliding_window(a, k, |x, y| x <= y)
+}
+
+/// 長さ k の各区間 `a[i..i + k]` の最大値（$O(N)$）
+pub fn sliding_window_max<T: PartialOrd + Clone>(a: &[T], k: usize) -> Vec<T> {
+    sliding_window(a, k, |x, y| x >= y)
+}
+
+/// `better(x, y)` が true なら y より x を残す単調キュー
+fn sliding_window<T: Clone, F: Fn(&T, &T) -> bool>(a: &[T], k: usize, better: F) -> Vec<T> {
+    assert!(k > 0);
+    let mut res = Vec::with_capacity((a.len() + 1).saturating_sub(k));
+    let mut deque: VecDeque<usize> = VecDeque::new();
+    for (i, x) in a.iter().enumerate() {
+        while deque.back().is_some_and(|&j| better(x, &a[j])) {
+            deque.pop_back();
+        }
+        deque.push_back(i);
+        if deque[0] + k <= i {
+            deque.pop_front();
+        }
+        if i + 1 >= k {
+            res.push(a[deque[0]].clone());
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Affine;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    type Mint = ModInt998244353;
+
+    fn random_affine(rng: &mut StdRng) -> (Mint, Mint) {
+        (
+            Mint::new(rng.random_range(0..10)),
+            Mint::new(rng.random_range(0..10)),
+        )
+    }
+
+    fn naive_fold<'a>(it: impl Iterator<Item = &'a (Mint, Mint)>) -> (Mint, Mint) {
+        it.fold(Affine::<Mint>::identity(), |f, g| Affine::<Mint>::op(&f, g))
+    }
+
+    #[test]
+    fn foldable_queue_test() {
+        let mut rng = StdRng::seed_from_u64(33);
+        let mut queue = FoldableQueue::<Affine<Mint>>::new();
+        let mut naive = VecDeque::new();
+        for _ in 0..3000 {
+            if rng.random_bool(0.55) {
+                let x = random_affine(&mut rng);
+                queue.push(x);
+                naive.push_back(x);
+            } else {
+                assert_eq!(queue.pop(), naive.pop_front());
+            }
+            assert_eq!(queue.len(), naive.len());
+            assert_eq!(queue.fold(), naive_fold(naive.iter()));
+        }
+    }
+
+    #[test]
+    fn foldable_deque_test() {
+        let mut rng = StdRng::seed_from_u64(34);
+        let mut deque = FoldableDeque::<Affine<Mint>>::new();
+        let mut naive = VecDeque::new();
+        for _ in 0..3000 {
+            match rng.random_range(0..4) {
+                0 => {
+                    let x = random_affine(&mut rng);
+                    deque.push_front(x);
+                    naive.push_front(x);
+                }
+                1 => {
+                    let x = random_affine(&mut rng);
+                    deque.push_back(x);
+                    naive.push_back(x);
+                }
+                2 => assert_eq!(deque.pop_front(), naive.pop_front()),
+                _ => assert_eq!(deque.pop_back(), naive.pop_back()),
+            }
+            assert_eq!(deque.len(), naive.len());
+            assert_eq!(deque.fold(), naive_fold(naive.iter()));
+        }
+    }
+
+    #[test]
+    fn sliding_window_test() {
+        let mut rng = StdRng::seed_from_u64(35);
+        for n in 0..30 {
+            let a: Vec<i64> = (0..n).map(|_| rng.random_range(-10..=10)).collect();
+            for k in 1..=n + 1 {
+                let min: Vec<i64> = a.windows(k).map(|w| *w.iter().min().unwrap()).collect();
+                let max: Vec<i64> = a.windows(k).map(|w| *w.iter().max().unwrap()).collect();
+                assert_eq!(sliding_window_min(&a, k), min);
+                assert_eq!(sliding_window_max(&a, k), max);
+            }
+        }
+    }
+}
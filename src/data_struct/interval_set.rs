@@ -0,0 +1,349 @@
+//! 区間の集合
+//!
+//! - `IntervalSet`: 互いに素な半開区間の集合。区間の追加・削除と、点を含む区間の取得・mex を$O(log(N))$（償却）で行う
+//! - `IntervalMap`: 区間ごとに値を持つ（いわゆる ODT / Chtholly tree）。区間への値の代入を$O(log(N))$（償却）で行う
+//!
+//! 更新のたびに実際に追加・削除された区間を返すので、BIT などを差分で更新できる
+
+use std::collections::BTreeMap;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+
+/// 互いに素な半開区間 $[l, r)$ の集合
+///
+/// 隣接する区間（$[a, b)$ と $[b, c)$）は1つにまとめて管理する
+#[derive(Clone, Debug)]
+pub struct IntervalSet<T: Ord + Clone> {
+    // 左端 -> 右端
+    ranges: BTreeMap<T, T>,
+}
+
+impl<T: Ord + Clone> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// 区間の個数
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// $[l, r)$ を追加し、新たに覆われた区間を昇順に返す
+    pub fn insert(&mut self, l: T, r: T) -> Vec<(T, T)> {
+        if l >= r {
+            return Vec::new();
+        }
+        // [l, r) と重なるか隣接する区間
+        let mut touched: Vec<(T, T)> = Vec::new();
+        if let Some((a, b)) = self.ranges.range(..&l).next_back() {
+            if *b >= l {
+                touched.push((a.clone(), b.clone()));
+            }
+        }
+        touched.extend(
+            self.ranges
+                .range(&l..=&r)
+                .map(|(a, b)| (a.clone(), b.clone())),
+        );
+
+        let mut added = Vec::new();
+        let mut cur = l.clone();
+        for (a, b) in touched.iter() {
+            if cur < *a {
+                added.push((cur.clone(), a.clone()));
+            }
+            if cur < *b {
+                cur = b.clone();
+            }
+        }
+        if cur < r {
+            added.push((cur, r.clone()));
+        }
+
+        let mut new_l = l;
+        let mut new_r = r;
+        for (a, b) in touched {
+            self.ranges.remove(&a);
+            if a < new_l {
+                new_l = a;
+            }
+            if b > new_r {
+                new_r = b;
+            }
+        }
+        self.ranges.insert(new_l, new_r);
+        added
+    }
+
+    /// $[l, r)$ を削除し、実際に削除された区間を昇順に返す
+    pub fn remove(&mut self, l: T, r: T) -> Vec<(T, T)> {
+        if l >= r {
+            return Vec::new();
+        }
+        let mut touched: Vec<(T, T)> = Vec::new();
+        if let Some((a, b)) = self.ranges.range(..&l).next_back() {
+            if *b > l {
+                touched.push((a.clone(), b.clone()));
+            }
+        }
+        touched.extend(
+            self.ranges
+                .range((Included(&l), Excluded(&r)))
+                .map(|(a, b)| (a.clone(), b.clone())),
+        );
+
+        let mut removed = Vec::with_capacity(touched.len());
+        for (a, b) in touched {
+            self.ranges.remove(&a);
+            let from = if a < l { l.clone() } else { a.clone() };
+            let to = if b > r { r.clone() } else { b.clone() };
+            removed.push((from, to));
+            if a < l {
+                self.ranges.insert(a, l.clone());
+            }
+            if r < b {
+                self.ranges.insert(r.clone(), b);
+            }
+        }
+        removed
+    }
+
+    /// x を含む区間
+    pub fn covering(&self, x: &T) -> Option<(&T, &T)> {
+        self.ranges.range(..=x).next_back().filter(|(_, b)| *b > x)
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.covering(x).is_some()
+    }
+
+    /// $[l, r)$ 全体が覆われているか
+    pub fn covers(&self, l: &T, r: &T) -> bool {
+        if l >= r {
+            return true;
+        }
+        match self.covering(l) {
+            Some((_, b)) => b >= r,
+            None => false,
+        }
+    }
+
+    /// x 以上で、どの区間にも含まれない最小の値
+    pub fn mex(&self, x: &T) -> T {
+        match self.covering(x) {
+            Some((_, b)) => b.clone(),
+            None => x.clone(),
+        }
+    }
+
+    /// 区間を昇順に列挙する
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &T)> {
+        self.ranges.iter()
+    }
+}
+
+/// 互いに素な半開区間ごとに値を持つ連想配列
+///
+/// 隣接していて値が等しい区間は1つにまとめて管理する
+#[derive(Clone, Debug)]
+pub struct IntervalMap<K: Ord + Clone, V: Clone + PartialEq> {
+    // 左端 -> (右端, 値)
+    ranges: BTreeMap<K, (K, V)>,
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> Default for IntervalMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> IntervalMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// 区間の個数
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// $[l, r)$ に v を代入し、上書きされた区間とその値を昇順に返す
+    pub fn assign(&mut self, l: K, r: K, v: V) -> Vec<(K, K, V)> {
+        if l >= r {
+            return Vec::new();
+        }
+        let removed = self.remove(l.clone(), r.clone());
+        let mut new_l = l;
+        let mut new_r = r;
+        if let Some((a, (b, w))) = self.ranges.range(..&new_l).next_back() {
+            if *b == new_l && *w == v {
+                new_l = a.clone();
+            }
+        }
+        if let Some((b, w)) = self.ranges.get(&new_r) {
+            if *w == v {
+                let b = b.clone();
+                self.ranges.remove(&new_r);
+                new_r = b;
+            }
+        }
+        self.ranges.insert(new_l, (new_r, v));
+        removed
+    }
+
+    /// $[l, r)$ の値を削除し、削除された区間とその値を昇順に返す
+    pub fn remove(&mut self, l: K, r: K) -> Vec<(K, K, V)> {
+        if l >= r {
+            return Vec::new();
+        }
+        self.split(&l);
+        self.split(&r);
+        let keys: Vec<K> = self
+            .ranges
+            .range((Included(&l), Excluded(&r)))
+            .map(|(a, _)| a.clone())
+            .collect();
+        keys.into_iter()
+            .map(|a| {
+                let (b, v) = self.ranges.remove(&a).unwrap();
+                (a, b, v)
+            })
+            .collect()
+    }
+
+    /// x を含む区間とその値
+    pub fn get(&self, x: &K) -> Option<(&K, &K, &V)> {
+        self.ranges
+            .range(..=x)
+            .next_back()
+            .filter(|(_, (b, _))| b > x)
+            .map(|(a, (b, v))| (a, b, v))
+    }
+
+    /// 区間とその値を昇順に列挙する
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &K, &V)> {
+        self.ranges.iter().map(|(a, (b, v))| (a, b, v))
+    }
+
+    /// x をまたぐ区間があれば x で分割する
+    fn split(&mut self, x: &K) {
+        let (a, b, v) = match self.ranges.range((Unbounded, Excluded(x))).next_back() {
+            Some((a, (b, v))) if b > x => (a.clone(), b.clone(), v.clone()),
+            _ => return,
+        };
+        self.ranges.insert(a, (x.clone(), v.clone()));
+        self.ranges.insert(x.clone(), (b, v));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 昇順の点の列を半開区間の列にまとめる
+    fn to_ranges(points: impl Iterator<Item = usize>) -> Vec<(usize, usize)> {
+        let mut res: Vec<(usize, usize)> = Vec::new();
+        for p in points {
+            match res.last_mut() {
+                Some(last) if last.1 == p => last.1 += 1,
+                _ => res.push((p, p + 1)),
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn interval_set_test() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let n = 30;
+        let mut set = IntervalSet::new();
+        let mut naive = vec![false; n + 1];
+        for _ in 0..2000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            if rng.random_bool(0.5) {
+                let expected = to_ranges((l..r).filter(|&i| !naive[i]));
+                assert_eq!(set.insert(l, r), expected);
+                naive[l..r].iter_mut().for_each(|b| *b = true);
+            } else {
+                let expected = to_ranges((l..r).filter(|&i| naive[i]));
+                assert_eq!(set.remove(l, r), expected);
+                naive[l..r].iter_mut().for_each(|b| *b = false);
+            }
+            let all: Vec<(usize, usize)> = set.iter().map(|(&a, &b)| (a, b)).collect();
+            assert_eq!(all, to_ranges((0..=n).filter(|&i| naive[i])));
+            let x = rng.random_range(0..=n);
+            assert_eq!(set.contains(&x), naive[x]);
+            assert_eq!(set.mex(&x), (x..).find(|&i| i > n || !naive[i]).unwrap());
+            assert_eq!(set.covers(&l, &r), naive[l..r].iter().all(|&b| b));
+        }
+    }
+
+    #[test]
+    fn interval_set_adjacent_test() {
+        let mut set = IntervalSet::new();
+        assert_eq!(set.insert(1, 3), vec![(1, 3)]);
+        assert_eq!(set.insert(5, 7), vec![(5, 7)]);
+        assert_eq!(set.insert(3, 5), vec![(3, 5)]);
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.covering(&4), Some((&1, &7)));
+        assert_eq!(set.remove(2, 4), vec![(2, 4)]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(&1, &2), (&4, &7)]);
+    }
+
+    #[test]
+    fn interval_map_test() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let n = 30;
+        let mut map = IntervalMap::new();
+        let mut naive: Vec<Option<u8>> = vec![None; n];
+        for _ in 0..2000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let v = rng.random_range(0..3);
+            let assign = rng.random_bool(0.8);
+            let removed = if assign {
+                map.assign(l, r, v)
+            } else {
+                map.remove(l, r)
+            };
+            // 上書きされた値を点ごとに復元して比較する
+            let mut old = vec![None; n];
+            for (a, b, w) in removed {
+                old[a..b].iter_mut().for_each(|x| *x = Some(w));
+            }
+            assert_eq!(old[l..r], naive[l..r]);
+            assert!(old[..l].iter().chain(old[r..].iter()).all(|x| x.is_none()));
+            let new = if assign { Some(v) } else { None };
+            naive[l..r].iter_mut().for_each(|x| *x = new);
+
+            for (i, x) in naive.iter().enumerate() {
+                assert_eq!(map.get(&i).map(|(_, _, &w)| w), *x);
+            }
+            // 隣接する同じ値の区間はまとめられている
+            let pieces: Vec<_> = map.iter().collect();
+            for w in pieces.windows(2) {
+                assert!(w[0].1 != w[1].0 || w[0].2 != w[1].2);
+            }
+        }
+    }
+}
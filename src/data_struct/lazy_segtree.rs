@@ -0,0 +1,382 @@
+//! 遅延評価セグメント木
+//!
+//! モノイド `M` の元の列と、`M` に作用するモノイド `F` に対して以下の様なことができる
+//! - 区間 $[l, r)$ の各要素に f を作用させる
+//! - 区間積の取得
+//! - 区間積に対する単調な条件での二分探索（`max_right`, `min_left`）
+//! - 計算量はいずれも$O(log(N))$
+//!
+//! よく使う組み合わせは型エイリアスとして用意してある。
+//! 総和を扱うものは各要素を `(値, 1)` として初期化すること（`SumWithLen` を参照）
+
+use super::algebra::{Action, Additive, Affine, Assign, Max, Min, Monoid, SumWithLen};
+use super::bit::range_to_pair;
+use std::marker::PhantomData;
+use std::ops::RangeBounds;
+
+pub type RangeAffineRangeSum<T> = LazySegtree<SumWithLen<T>, Affine<T>>;
+pub type RangeAddRangeSum<T> = LazySegtree<SumWithLen<T>, Additive<T>>;
+pub type RangeAddRangeMin<T> = LazySegtree<Min<T>, Additive<T>>;
+pub type RangeAddRangeMax<T> = LazySegtree<Max<T>, Additive<T>>;
+pub type RangeAssignRangeSum<T> = LazySegtree<SumWithLen<T>, Assign<T>>;
+pub type RangeAssignRangeMin<T> = LazySegtree<Min<T>, Assign<T>>;
+pub type RangeAssignRangeMax<T> = LazySegtree<Max<T>, Assign<T>>;
+pub type RangeChminRangeMin<T> = LazySegtree<Min<T>, Min<T>>;
+pub type RangeChmaxRangeMax<T> = LazySegtree<Max<T>, Max<T>>;
+
+pub struct LazySegtree<M: Monoid, F: Action<M>> {
+    n: usize,
+    size: usize,
+    log: usize,
+    d: Vec<M::S>,
+    lz: Vec<F::S>,
+    _phantom: PhantomData<(M, F)>,
+}
+
+impl<M: Monoid, F: Action<M>> Clone for LazySegtree<M, F> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            size: self.size,
+            log: self.log,
+            d: self.d.clone(),
+            lz: self.lz.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<M: Monoid, F: Action<M>> From<Vec<M::S>> for LazySegtree<M, F> {
+    fn from(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        let mut d = vec![M::identity(); 2 * size];
+        for (i, x) in v.into_iter().enumerate() {
+            d[size + i] = x;
+        }
+        let mut seg = Self {
+            n,
+            size,
+            log,
+            d,
+            lz: vec![F::identity(); size],
+            _phantom: PhantomData,
+        };
+        for i in (1..size).rev() {
+            seg.update(i);
+        }
+        seg
+    }
+}
+
+impl<M: Monoid, F: Action<M>> LazySegtree<M, F> {
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_p$ を x に更新する
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    pub fn get(&mut self, p: usize) -> M::S {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p].clone()
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&mut self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        let mut l = l + self.size;
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+        let mut sml = M::identity();
+        let mut smr = M::identity();
+        while l < r {
+            if l & 1 == 1 {
+                sml = M::op(&sml, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = M::op(&self.d[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&sml, &smr)
+    }
+
+    pub fn all_prod(&self) -> M::S {
+        self.d[1].clone()
+    }
+
+    /// 区間 `range` の各要素に f を作用させる
+    pub fn apply<R: RangeBounds<usize>>(&mut self, range: R, f: F::S) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return;
+        }
+        let l = l + self.size;
+        let r = r + self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+        {
+            let mut l = l;
+            let mut r = r;
+            while l < r {
+                if l & 1 == 1 {
+                    self.all_apply(l, &f);
+                    l += 1;
+                }
+                if r & 1 == 1 {
+                    r -= 1;
+                    self.all_apply(r, &f);
+                }
+                l >>= 1;
+                r >>= 1;
+            }
+        }
+        for i in 1..=self.log {
+            if ((l >> i) << i) != l {
+                self.update(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.update((r - 1) >> i);
+            }
+        }
+    }
+
+    /// `g(&self.prod(l..r))` が true となる最大の r を求める
+    ///
+    /// g は単調で、`g(&M::identity())` は true であること
+    pub fn max_right<G: Fn(&M::S) -> bool>(&mut self, l: usize, g: G) -> usize {
+        assert!(l <= self.n);
+        assert!(g(&M::identity()));
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(l >> i);
+        }
+        let mut sm = M::identity();
+        loop {
+            while l & 1 == 0 {
+                l >>= 1;
+            }
+            if !g(&M::op(&sm, &self.d[l])) {
+                while l < self.size {
+                    self.push(l);
+                    l *= 2;
+                    let res = M::op(&sm, &self.d[l]);
+                    if g(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = M::op(&sm, &self.d[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                break;
+            }
+        }
+        self.n
+    }
+
+    /// `g(&self.prod(l..r))` が true となる最小の l を求める
+    ///
+    /// g は単調で、`g(&M::identity())` は true であること
+    pub fn min_left<G: Fn(&M::S) -> bool>(&mut self, r: usize, g: G) -> usize {
+        assert!(r <= self.n);
+        assert!(g(&M::identity()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        for i in (1..=self.log).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut sm = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r & 1 == 1 {
+                r >>= 1;
+            }
+            if !g(&M::op(&self.d[r], &sm)) {
+                while r < self.size {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let res = M::op(&self.d[r], &sm);
+                    if g(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = M::op(&self.d[r], &sm);
+            if r & r.wrapping_neg() == r {
+                break;
+            }
+        }
+        0
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = M::op(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+
+    fn all_apply(&mut self, k: usize, f: &F::S) {
+        self.d[k] = F::act(f, &self.d[k]);
+        if k < self.size {
+            self.lz[k] = F::op(&self.lz[k], f);
+        }
+    }
+
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lz[k], F::identity());
+        self.all_apply(2 * k, &f);
+        self.all_apply(2 * k + 1, &f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn range_affine_range_sum_test() {
+        type Mint = ModInt998244353;
+        let mut rng = StdRng::seed_from_u64(7);
+        let n = 30;
+        let mut naive: Vec<Mint> = (0..n)
+            .map(|_| Mint::new(rng.random_range(0..1000)))
+            .collect();
+        let mut seg = RangeAffineRangeSum::<Mint>::from(
+            naive.iter().map(|&x| (x, Mint::new(1))).collect::<Vec<_>>(),
+        );
+        for _ in 0..1000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            if rng.random_bool(0.5) {
+                let a = Mint::new(rng.random_range(0..1000));
+                let b = Mint::new(rng.random_range(0..1000));
+                seg.apply(l..r, (a, b));
+                naive[l..r].iter_mut().for_each(|x| *x = a * *x + b);
+            } else {
+                let expected = naive[l..r].iter().fold(Mint::new(0), |s, &x| s + x);
+                assert_eq!(seg.prod(l..r), (expected, Mint::new((r - l) as u64)));
+            }
+        }
+    }
+
+    #[test]
+    fn range_add_range_min_test() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let n = 30;
+        let mut naive = vec![0i64; n];
+        let mut seg = RangeAddRangeMin::<i64>::from(naive.clone());
+        for _ in 0..1000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            if rng.random_bool(0.5) {
+                let x = rng.random_range(-100..=100);
+                seg.apply(l..r, x);
+                naive[l..r].iter_mut().for_each(|a| *a += x);
+            } else {
+                let min =
+                    |l: usize, r: usize| naive[l..r].iter().copied().min().unwrap_or(i64::MAX);
+                assert_eq!(seg.prod(l..r), min(l, r));
+                let bound = rng.random_range(-300..=300);
+                let expected = (l..=n).rev().find(|&r| min(l, r) >= bound).unwrap();
+                assert_eq!(seg.max_right(l, |&x| x >= bound), expected);
+                let expected = (0..=r).find(|&l| min(l, r) >= bound).unwrap();
+                assert_eq!(seg.min_left(r, |&x| x >= bound), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn range_assign_test() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let n = 30;
+        let mut naive = vec![0i64; n];
+        let mut assign_sum = RangeAssignRangeSum::<i64>::from(vec![(0, 1); n]);
+        let mut assign_max = RangeAssignRangeMax::<i64>::from(naive.clone());
+        for _ in 0..1000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let x = rng.random_range(-100..=100);
+            assign_sum.apply(l..r, Some(x));
+            assign_max.apply(l..r, Some(x));
+            naive[l..r].iter_mut().for_each(|a| *a = x);
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            assert_eq!(assign_sum.prod(l..r).0, naive[l..r].iter().sum::<i64>());
+            assert_eq!(
+                assign_max.prod(l..r),
+                naive[l..r].iter().copied().max().unwrap_or(i64::MIN)
+            );
+        }
+        // 複製は元の木と独立している
+        let mut cloned = assign_max.clone();
+        assign_max.apply(.., Some(1000));
+        assert_eq!(cloned.prod(..), naive.iter().copied().max().unwrap());
+        assert_eq!(assign_max.prod(..), 1000);
+    }
+
+    #[test]
+    fn range_chmin_range_min_test() {
+        let mut chmin_min = RangeChminRangeMin::<i64>::from(vec![5, 1, 7, 3]);
+        chmin_min.apply(2.., 4);
+        assert_eq!(chmin_min.get(2), 4);
+        assert_eq!(chmin_min.prod(2..), 3);
+        chmin_min.apply(..2, 0);
+        assert_eq!(chmin_min.prod(..), 0);
+    }
+}
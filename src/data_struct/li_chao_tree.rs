@@ -0,0 +1,203 @@
+//! Li Chao Tree
+//!
+//! あらかじめ与えた x 座標の集合上で、直線・線分の追加と x での最小値を$O(log(N))$で求める
+//! （線分の追加は$O(log(N)^2)$）
+//!
+//! 座標の集合を与えるので、疎な座標でも圧縮した形でそのまま扱える。
+//! 最大値が欲しい場合は a, b の符号を反転して追加し、結果の符号を反転すること
+//!
+//! `convex_hull_trick` と同じく、a, b の絶対値は `LineNum::MAX_COEF` 以下であること。
+//! 符号を反転しても範囲に収まるので、最大値のための反転もオーバーフローしない
+
+use super::convex_hull_trick::{check_coef, LineNum};
+
+#[derive(Clone, Debug)]
+pub struct LiChaoTree<T: LineNum> {
+    // 昇順に並べた x 座標
+    xs: Vec<T>,
+    size: usize,
+    lines: Vec<Option<(T, T)>>,
+}
+
+impl<T: LineNum> LiChaoTree<T> {
+    /// クエリで使う x 座標の集合を与えて初期化する（重複していてもよい）
+    pub fn new(xs: &[T]) -> Self {
+        let mut xs = xs.to_vec();
+        xs.sort_by(|a, b| a.partial_cmp(b).expect("NaN is not allowed"));
+        xs.dedup();
+        let size = xs.len().next_power_of_two();
+        Self {
+            xs,
+            size,
+            lines: vec![None; 2 * size],
+        }
+    }
+
+    /// x 座標の個数
+    pub fn len(&self) -> usize {
+        self.xs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+
+    /// 直線 $y = ax + b$ を追加する
+    pub fn add_line(&mut self, a: T, b: T) {
+        check_coef(a, b);
+        if !self.xs.is_empty() {
+            self.insert(1, 0, self.size, (a, b));
+        }
+    }
+
+    /// 線分 $y = ax + b \ (x_l \leq x < x_r)$ を追加する
+    pub fn add_segment(&mut self, a: T, b: T, xl: T, xr: T) {
+        check_coef(a, b);
+        let mut l = self.xs.partition_point(|&x| x < xl);
+        let mut r = self.xs.partition_point(|&x| x < xr);
+        let mut width = 1;
+        l += self.size;
+        r += self.size;
+        // 下から順にノードを見ていき、区間に完全に含まれるノードへ挿入する
+        while l < r {
+            if l & 1 == 1 {
+                self.insert_at(l, width, (a, b));
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                self.insert_at(r, width, (a, b));
+            }
+            l >>= 1;
+            r >>= 1;
+            width <<= 1;
+        }
+    }
+
+    /// x での最小値。x は初期化時に与えた座標であること
+    pub fn query(&self, x: T) -> Option<T> {
+        let i = self.xs.partition_point(|&v| v < x);
+        assert!(i < self.xs.len() && self.xs[i] == x, "x is not registered");
+        let mut k = i + self.size;
+        let mut res: Option<T> = None;
+        while k > 0 {
+            if let Some(l) = self.lines[k] {
+                let y = l.0 * x + l.1;
+                if res.map_or(true, |r| y < r) {
+                    res = Some(y);
+                }
+            }
+            k >>= 1;
+        }
+        res
+    }
+
+    /// ノード k（幅 width）に対応する区間へ直線を挿入する
+    fn insert_at(&mut self, k: usize, width: usize, line: (T, T)) {
+        let lo = k * width - self.size;
+        self.insert(k, lo, lo + width, line);
+    }
+
+    fn x(&self, i: usize) -> T {
+        // 2 冪に揃えるための余りの位置は最後の座標として扱う
+        self.xs[i.min(self.xs.len() - 1)]
+    }
+
+    fn insert(&mut self, mut k: usize, mut lo: usize, mut hi: usize, mut line: (T, T)) {
+        let f = |l: (T, T), x: T| l.0 * x + l.1;
+        loop {
+            let cur = match self.lines[k] {
+                Some(cur) => cur,
+                None => {
+                    self.lines[k] = Some(line);
+                    return;
+                }
+            };
+            let mid = (lo + hi) / 2;
+            let (xl, xm, xr) = (self.x(lo), self.x(mid), self.x(hi - 1));
+            let left = f(line, xl) < f(cur, xl);
+            let right = f(line, xr) < f(cur, xr);
+            if left && right {
+                self.lines[k] = Some(line);
+                return;
+            }
+            if !left && !right {
+                return;
+            }
+            // ノードには mid で小さい方を残し、もう一方を片側の子へ降ろす
+            if f(line, xm) < f(cur, xm) {
+                self.lines[k] = Some(line);
+                line = cur;
+            }
+            if hi - lo == 1 {
+                return;
+            }
+            if f(line, xl) < f(self.lines[k].unwrap(), xl) {
+                k *= 2;
+                hi = mid;
+            } else {
+                k = 2 * k + 1;
+                lo = mid;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn li_chao_tree_test() {
+        let mut rng = StdRng::seed_from_u64(23);
+        for _ in 0..30 {
+            let n = rng.random_range(1..=20);
+            let xs: Vec<i64> = (0..n).map(|_| rng.random_range(-50..=50)).collect();
+            let mut tree = LiChaoTree::new(&xs);
+            // (a, b, xl, xr)
+            let mut segs: Vec<(i64, i64, i64, i64)> = Vec::new();
+            for _ in 0..40 {
+                let a = rng.random_range(-20..=20);
+                let b = rng.random_range(-100..=100);
+                if rng.random_bool(0.5) {
+                    tree.add_line(a, b);
+                    segs.push((a, b, i64::MIN, i64::MAX));
+                } else {
+                    let xl = rng.random_range(-60..=60);
+                    let xr = rng.random_range(xl..=60);
+                    tree.add_segment(a, b, xl, xr);
+                    segs.push((a, b, xl, xr));
+                }
+                for &x in xs.iter() {
+                    let expected = segs
+                        .iter()
+                        .filter(|&&(_, _, l, r)| l <= x && x < r)
+                        .map(|&(a, b, _, _)| a * x + b)
+                        .min();
+                    assert_eq!(tree.query(x), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn li_chao_tree_float_test() {
+        let mut tree = LiChaoTree::new(&[0.0, 1.0, 2.5, 1e9]);
+        tree.add_line(1.0, 0.0);
+        tree.add_segment(-1.0, 3.0, 1.0, 3.0);
+        assert_eq!(tree.query(0.0), Some(0.0));
+        assert_eq!(tree.query(1.0), Some(1.0));
+        assert_eq!(tree.query(2.5), Some(0.5));
+        assert_eq!(tree.query(1e9), Some(1e9));
+    }
+
+    #[test]
+    #[should_panic]
+    fn li_chao_tree_max_coef_test() {
+        let mut lct = LiChaoTree::<i64>::new(&[0, 1]);
+        lct.add_line(i64::MAX_COEF, 0);
+        lct.add_segment(0, i64::MIN, 0, 1);
+    }
+}
@@ -0,0 +1,390 @@
+//! Link-Cut Tree
+//!
+//! 頂点に モノイド `M` の値を持つ森に対して、以下の様なことを償却$O(log(N))$で行う
+//! - 辺の追加（`link`）・削除（`cut`）
+//! - 根の付け替え（`evert`）と、根・LCA の取得
+//! - 頂点の値の更新と、パス上の値の総積（パスの向きの順）
+//!
+//! 部分木の値の総積が欲しい場合は `EulerTourTree` を使う
+
+use super::algebra::Monoid;
+
+const NIL: usize = usize::MAX;
+
+#[derive(Clone)]
+struct Node<S> {
+    left: usize,
+    right: usize,
+    parent: usize,
+    rev: bool,
+    val: S,
+    // 部分木の総積（左から順）と、逆順の総積
+    prod: S,
+    rev_prod: S,
+}
+
+pub struct LinkCutTree<M: Monoid> {
+    nodes: Vec<Node<M::S>>,
+}
+
+impl<M: Monoid> Clone for LinkCutTree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> From<Vec<M::S>> for LinkCutTree<M> {
+    fn from(v: Vec<M::S>) -> Self {
+        let nodes = v
+            .into_iter()
+            .map(|x| Node {
+                left: NIL,
+                right: NIL,
+                parent: NIL,
+                rev: false,
+                val: x.clone(),
+                prod: x.clone(),
+                rev_prod: x,
+            })
+            .collect();
+        Self { nodes }
+    }
+}
+
+impl<M: Monoid> LinkCutTree<M> {
+    /// 頂点数 n、辺のない森
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// 辺 (u, v) を追加する。u と v は別の木にあること
+    ///
+    /// できた木の根は v を含んでいた木の根になる
+    pub fn link(&mut self, u: usize, v: usize) {
+        assert!(!self.connected(u, v), "u and v are already connected");
+        self.evert(u);
+        self.nodes[u].parent = v;
+    }
+
+    /// 辺 (u, v) を削除する。辺 (u, v) が存在すること
+    ///
+    /// 分かれた2つの木の根はそれぞれ u, v になる
+    pub fn cut(&mut self, u: usize, v: usize) {
+        self.evert(u);
+        self.access(v);
+        assert!(
+            self.nodes[v].left == u && self.nodes[u].right == NIL,
+            "edge (u, v) does not exist"
+        );
+        self.nodes[v].left = NIL;
+        self.nodes[u].parent = NIL;
+        self.update(v);
+    }
+
+    /// v を木の根にする
+    pub fn evert(&mut self, v: usize) {
+        self.access(v);
+        self.toggle(v);
+        self.push(v);
+    }
+
+    /// v を含む木の根
+    pub fn root(&mut self, v: usize) -> usize {
+        self.access(v);
+        let mut r = v;
+        loop {
+            self.push(r);
+            match self.nodes[r].left {
+                NIL => break,
+                l => r = l,
+            }
+        }
+        self.splay(r);
+        r
+    }
+
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        u == v || self.root(u) == self.root(v)
+    }
+
+    /// 現在の根に関する u と v の LCA。別の木にあれば `None`
+    pub fn lca(&mut self, u: usize, v: usize) -> Option<usize> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.access(u);
+        Some(self.access(v))
+    }
+
+    /// 頂点 v の値を x に更新する
+    pub fn set(&mut self, v: usize, x: M::S) {
+        self.access(v);
+        self.nodes[v].val = x;
+        self.update(v);
+    }
+
+    pub fn get(&self, v: usize) -> M::S {
+        self.nodes[v].val.clone()
+    }
+
+    /// u から v へのパス上の頂点の値の総積（u 側から順）。別の木にあれば `None`
+    ///
+    /// 木の根は呼び出し前のまま変わらない
+    pub fn prod_path(&mut self, u: usize, v: usize) -> Option<M::S> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        let root = self.root(u);
+        self.evert(u);
+        self.access(v);
+        let res = self.nodes[v].prod.clone();
+        self.evert(root);
+        Some(res)
+    }
+
+    /// v から根へのパスを1本の splay 木にまとめ、直前に触れていた splay 木の根を返す
+    fn access(&mut self, v: usize) -> usize {
+        let mut last = NIL;
+        let mut cur = v;
+        while cur != NIL {
+            self.splay(cur);
+            self.nodes[cur].right = last;
+            self.update(cur);
+            last = cur;
+            cur = self.nodes[cur].parent;
+        }
+        self.splay(v);
+        last
+    }
+
+    fn is_root(&self, x: usize) -> bool {
+        let p = self.nodes[x].parent;
+        p == NIL || (self.nodes[p].left != x && self.nodes[p].right != x)
+    }
+
+    fn update(&mut self, x: usize) {
+        let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+        let mut prod = self.nodes[x].val.clone();
+        let mut rev_prod = prod.clone();
+        if l != NIL {
+            prod = M::op(&self.nodes[l].prod, &prod);
+            rev_prod = M::op(&rev_prod, &self.nodes[l].rev_prod);
+        }
+        if r != NIL {
+            prod = M::op(&prod, &self.nodes[r].prod);
+            rev_prod = M::op(&self.nodes[r].rev_prod, &rev_prod);
+        }
+        self.nodes[x].prod = prod;
+        self.nodes[x].rev_prod = rev_prod;
+    }
+
+    fn toggle(&mut self, x: usize) {
+        let node = &mut self.nodes[x];
+        std::mem::swap(&mut node.left, &mut node.right);
+        std::mem::swap(&mut node.prod, &mut node.rev_prod);
+        node.rev ^= true;
+    }
+
+    fn push(&mut self, x: usize) {
+        if self.nodes[x].rev {
+            self.nodes[x].rev = false;
+            let (l, r) = (self.nodes[x].left, self.nodes[x].right);
+            if l != NIL {
+                self.toggle(l);
+            }
+            if r != NIL {
+                self.toggle(r);
+            }
+        }
+    }
+
+    fn rotate(&mut self, x: usize) {
+        let p = self.nodes[x].parent;
+        let g = self.nodes[p].parent;
+        if self.nodes[p].left == x {
+            let b = self.nodes[x].right;
+            self.nodes[p].left = b;
+            if b != NIL {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].right = p;
+        } else {
+            let b = self.nodes[x].left;
+            self.nodes[p].right = b;
+            if b != NIL {
+                self.nodes[b].parent = p;
+            }
+            self.nodes[x].left = p;
+        }
+        if g != NIL {
+            if self.nodes[g].left == p {
+                self.nodes[g].left = x;
+            } else if self.nodes[g].right == p {
+                self.nodes[g].right = x;
+            }
+        }
+        self.nodes[p].parent = x;
+        self.nodes[x].parent = g;
+        self.update(p);
+        self.update(x);
+    }
+
+    fn splay(&mut self, x: usize) {
+        // 上から順に反転を伝える
+        let mut path = vec![x];
+        let mut y = x;
+        while !self.is_root(y) {
+            y = self.nodes[y].parent;
+            path.push(y);
+        }
+        for &y in path.iter().rev() {
+            self.push(y);
+        }
+        while !self.is_root(x) {
+            let p = self.nodes[x].parent;
+            if !self.is_root(p) {
+                let g = self.nodes[p].parent;
+                let zig_zig = (self.nodes[g].left == p) == (self.nodes[p].left == x);
+                if zig_zig {
+                    self.rotate(p);
+                } else {
+                    self.rotate(x);
+                }
+            }
+            self.rotate(x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Affine;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    type Mint = ModInt998244353;
+
+    /// u から v へのパス（別の木なら None）
+    fn naive_path(adj: &[BTreeSet<usize>], u: usize, v: usize) -> Option<Vec<usize>> {
+        let mut prev = vec![usize::MAX; adj.len()];
+        prev[u] = u;
+        let mut stack = vec![u];
+        while let Some(x) = stack.pop() {
+            for &y in adj[x].iter() {
+                if prev[y] == usize::MAX {
+                    prev[y] = x;
+                    stack.push(y);
+                }
+            }
+        }
+        if prev[v] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![v];
+        while *path.last().unwrap() != u {
+            path.push(prev[*path.last().unwrap()]);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    #[test]
+    fn link_cut_tree_test() {
+        let mut rng = StdRng::seed_from_u64(37);
+        let n = 15;
+        let mut vals: Vec<(Mint, Mint)> = (0..n)
+            .map(|_| {
+                (
+                    Mint::new(rng.random_range(1..10)),
+                    Mint::new(rng.random_range(0..10)),
+                )
+            })
+            .collect();
+        let mut lct = LinkCutTree::<Affine<Mint>>::from(vals.clone());
+        let mut adj = vec![BTreeSet::new(); n];
+        for _ in 0..3000 {
+            let u = rng.random_range(0..n);
+            let v = rng.random_range(0..n);
+            match rng.random_range(0..5) {
+                0 => {
+                    let connected = naive_path(&adj, u, v).is_some();
+                    assert_eq!(lct.connected(u, v), connected);
+                    if !connected {
+                        lct.link(u, v);
+                        adj[u].insert(v);
+                        adj[v].insert(u);
+                    }
+                }
+                1 => {
+                    if let Some(&w) = adj[u].iter().next() {
+                        lct.cut(u, w);
+                        adj[u].remove(&w);
+                        adj[w].remove(&u);
+                    }
+                }
+                2 => {
+                    let x = (
+                        Mint::new(rng.random_range(1..10)),
+                        Mint::new(rng.random_range(0..10)),
+                    );
+                    lct.set(u, x);
+                    vals[u] = x;
+                }
+                3 => {
+                    lct.evert(u);
+                    assert_eq!(lct.root(v) == u, naive_path(&adj, u, v).is_some());
+                }
+                _ => {
+                    let expected = naive_path(&adj, u, v).map(|path| {
+                        path.iter().fold(Affine::<Mint>::identity(), |f, &w| {
+                            Affine::<Mint>::op(&f, &vals[w])
+                        })
+                    });
+                    let root = lct.root(u);
+                    assert_eq!(lct.prod_path(u, v), expected);
+                    assert_eq!(lct.root(u), root);
+                }
+            }
+            assert_eq!(lct.get(u), vals[u]);
+        }
+    }
+
+    #[test]
+    fn link_cut_tree_lca_test() {
+        // 0 - 1 - 2, 1 - 3 - 4
+        let mut lct = LinkCutTree::<Affine<Mint>>::new(6);
+        lct.link(0, 1);
+        lct.link(1, 2);
+        lct.link(1, 3);
+        lct.link(3, 4);
+        lct.evert(0);
+        assert_eq!(lct.lca(2, 4), Some(1));
+        assert_eq!(lct.lca(4, 3), Some(3));
+        assert_eq!(lct.lca(0, 5), None);
+        lct.evert(4);
+        assert_eq!(lct.lca(0, 2), Some(1));
+        assert_eq!(lct.root(2), 4);
+        // パスの総積を取っても根は変わらない
+        assert!(lct.prod_path(0, 2).is_some());
+        assert_eq!(lct.root(0), 4);
+        assert_eq!(lct.lca(0, 2), Some(1));
+        assert_eq!(lct.lca(3, 0), Some(3));
+        lct.cut(1, 3);
+        assert_eq!(lct.root(2), 1);
+        assert_eq!(lct.root(4), 3);
+        assert!(!lct.connected(2, 4));
+    }
+}
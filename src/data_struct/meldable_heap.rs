@@ -0,0 +1,260 @@
+//! 併合可能ヒープ
+//!
+//! キーが最小の要素を取り出すヒープで、2つのヒープの併合を$O(log(N))$で行う
+//! - `SkewHeap`: 計算量は償却$O(log(N))$
+//! - `LeftistHeap`: 計算量は最悪$O(log(N))$
+//!
+//! どちらも全要素のキーへの加算を遅延評価で$O(1)$で行える（木上で子のヒープを併合していく DP 向け）
+
+use num::Zero;
+use std::ops::Add;
+
+struct Node<K, V> {
+    key: K,
+    val: V,
+    // 子孫のキーにまだ加えていない値
+    lazy: K,
+    // 右の背骨の長さ（LeftistHeap のみで使う）
+    rank: usize,
+    left: Option<Box<Node<K, V>>>,
+    right: Option<Box<Node<K, V>>>,
+}
+
+impl<K: Ord + Copy + Add<Output = K> + Zero, V> Node<K, V> {
+    fn new(key: K, val: V) -> Box<Self> {
+        Box::new(Self {
+            key,
+            val,
+            lazy: K::zero(),
+            rank: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn add(&mut self, x: K) {
+        self.key = self.key + x;
+        self.lazy = self.lazy + x;
+    }
+
+    fn push(&mut self) {
+        if !self.lazy.is_zero() {
+            let lazy = std::mem::replace(&mut self.lazy, K::zero());
+            if let Some(l) = self.left.as_mut() {
+                l.add(lazy);
+            }
+            if let Some(r) = self.right.as_mut() {
+                r.add(lazy);
+            }
+        }
+    }
+}
+
+fn rank<K, V>(node: &Option<Box<Node<K, V>>>) -> usize {
+    node.as_ref().map_or(0, |n| n.rank)
+}
+
+/// 右の背骨に沿って併合する。`leftist` が false なら skew heap として毎回子を入れ替える
+fn meld<K: Ord + Copy + Add<Output = K> + Zero, V>(
+    mut a: Option<Box<Node<K, V>>>,
+    mut b: Option<Box<Node<K, V>>>,
+    leftist: bool,
+) -> Option<Box<Node<K, V>>> {
+    let mut spine = Vec::new();
+    let mut rest = loop {
+        match (a, b) {
+            (None, x) | (x, None) => break x,
+            (Some(x), Some(y)) => {
+                let (mut small, big) = if x.key <= y.key { (x, y) } else { (y, x) };
+                small.push();
+                a = small.right.take();
+                b = Some(big);
+                spine.push(small);
+            }
+        }
+    };
+    while let Some(mut node) = spine.pop() {
+        node.right = rest;
+        if !leftist || rank(&node.left) < rank(&node.right) {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+        node.rank = rank(&node.right) + 1;
+        rest = Some(node);
+    }
+    rest
+}
+
+/// 深い木の再帰的な drop でスタックを溢れさせないよう、ループで解放する
+fn drop_iter<K, V>(root: Option<Box<Node<K, V>>>) {
+    let mut stack: Vec<Box<Node<K, V>>> = root.into_iter().collect();
+    while let Some(mut node) = stack.pop() {
+        stack.extend(node.left.take());
+        stack.extend(node.right.take());
+    }
+}
+
+macro_rules! impl_meldable_heap {
+    ($name: ident, $leftist: expr) => {
+        impl<K: Ord + Copy + Add<Output = K> + Zero, V> Default for $name<K, V> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<K, V> Drop for $name<K, V> {
+            fn drop(&mut self) {
+                drop_iter(self.root.take());
+            }
+        }
+
+        impl<K: Ord + Copy + Add<Output = K> + Zero, V> $name<K, V> {
+            pub fn new() -> Self {
+                Self { root: None, len: 0 }
+            }
+
+            pub fn len(&self) -> usize {
+                self.len
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+
+            pub fn push(&mut self, key: K, val: V) {
+                let root = self.root.take();
+                self.root = meld(root, Some(Node::new(key, val)), $leftist);
+                self.len += 1;
+            }
+
+            /// キーが最小の要素
+            pub fn peek(&self) -> Option<(K, &V)> {
+                self.root.as_ref().map(|n| (n.key, &n.val))
+            }
+
+            /// キーが最小の要素を取り出す
+            pub fn pop(&mut self) -> Option<(K, V)> {
+                let mut root = self.root.take()?;
+                root.push();
+                let (left, right) = (root.left.take(), root.right.take());
+                self.root = meld(left, right, $leftist);
+                self.len -= 1;
+                let Node { key, val, .. } = *root;
+                Some((key, val))
+            }
+
+            /// other の要素をすべて移す
+            pub fn append(&mut self, other: &mut Self) {
+                let (a, b) = (self.root.take(), other.root.take());
+                self.root = meld(a, b, $leftist);
+                self.len += std::mem::replace(&mut other.len, 0);
+            }
+
+            /// 全要素のキーに x を加える
+            pub fn add_all(&mut self, x: K) {
+                if let Some(root) = self.root.as_mut() {
+                    root.add(x);
+                }
+            }
+        }
+    };
+}
+
+/// Skew Heap（最小ヒープ）
+pub struct SkewHeap<K, V = ()> {
+    root: Option<Box<Node<K, V>>>,
+    len: usize,
+}
+
+impl_meldable_heap!(SkewHeap, false);
+
+/// Leftist Heap（最小ヒープ）
+pub struct LeftistHeap<K, V = ()> {
+    root: Option<Box<Node<K, V>>>,
+    len: usize,
+}
+
+impl_meldable_heap!(LeftistHeap, true);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    macro_rules! heap_test {
+        ($name: ident, $heap: ident, $seed: expr) => {
+            #[test]
+            fn $name() {
+                let mut rng = StdRng::seed_from_u64($seed);
+                let m = 5;
+                let mut heaps: Vec<$heap<i64>> = (0..m).map(|_| $heap::new()).collect();
+                let mut naive: Vec<BinaryHeap<Reverse<i64>>> =
+                    (0..m).map(|_| BinaryHeap::new()).collect();
+                for _ in 0..3000 {
+                    let i = rng.random_range(0..m);
+                    match rng.random_range(0..5) {
+                        0 | 1 => {
+                            let key = rng.random_range(-100..=100);
+                            heaps[i].push(key, ());
+                            naive[i].push(Reverse(key));
+                        }
+                        2 => {
+                            let expected = naive[i].pop().map(|Reverse(x)| x);
+                            assert_eq!(heaps[i].pop().map(|x| x.0), expected);
+                        }
+                        3 => {
+                            let j = rng.random_range(0..m);
+                            if i != j {
+                                let mut other = std::mem::take(&mut heaps[j]);
+                                heaps[i].append(&mut other);
+                                assert!(other.is_empty());
+                                let other: Vec<_> = naive[j].drain().collect();
+                                naive[i].extend(other);
+                            }
+                        }
+                        _ => {
+                            let x = rng.random_range(-10..=10);
+                            heaps[i].add_all(x);
+                            let added: Vec<_> =
+                                naive[i].drain().map(|Reverse(k)| Reverse(k + x)).collect();
+                            naive[i].extend(added);
+                        }
+                    }
+                    assert_eq!(heaps[i].len(), naive[i].len());
+                    assert_eq!(heaps[i].peek().map(|x| x.0), naive[i].peek().map(|x| x.0));
+                }
+            }
+        };
+    }
+
+    heap_test!(skew_heap_test, SkewHeap, 30);
+    heap_test!(leftist_heap_test, LeftistHeap, 31);
+
+    #[test]
+    fn heap_value_test() {
+        let mut a = LeftistHeap::new();
+        a.push(3, "c");
+        a.push(1, "a");
+        let mut b = LeftistHeap::new();
+        b.push(2, "b");
+        b.add_all(2);
+        a.append(&mut b);
+        a.add_all(10);
+        assert_eq!(a.pop(), Some((11, "a")));
+        assert_eq!(a.pop(), Some((13, "c")));
+        assert_eq!(a.pop(), Some((14, "b")));
+        assert_eq!(a.pop(), None);
+    }
+
+    #[test]
+    fn deep_heap_drop_test() {
+        let mut heap = SkewHeap::new();
+        for i in 0..200_000i64 {
+            heap.push(-i, ());
+        }
+        assert_eq!(heap.peek(), Some((-199_999, &())));
+        drop(heap);
+    }
+}
@@ -1,2 +1,23 @@
+pub mod algebra;
 pub mod union_find;
-pub mod bit;
\ No newline at end of file
+pub mod bit;
+pub mod range_bit;
+pub mod bit_2d;
+pub mod segtree;
+pub mod lazy_segtree;
+pub mod offline_dynamic_connectivity;
+pub mod sparse_table;
+pub mod wavelet_matrix;
+pub mod treap;
+pub mod interval_set;
+pub mod convex_hull_trick;
+pub mod li_chao_tree;
+pub mod persistent_segtree;
+pub mod dynamic_segtree;
+pub mod segtree_beats;
+pub mod meldable_heap;
+pub mod radix_heap;
+pub mod foldable_queue;
+pub mod binary_trie;
+pub mod link_cut_tree;
+pub mod euler_tour_tree;
@@ -0,0 +1,155 @@
+//! オフライン動的連結性判定
+//!
+//! 辺の追加・削除と「2頂点が連結か」という質問の列を先読みして、すべての質問に答える
+//! - 各辺が存在する時間区間を時間軸上のセグメント木の$O(log(Q))$個のノードに載せ、
+//!   `RollbackUnionFind` で併合と巻き戻しをしながら DFS する
+//! - 計算量は$O(Q log(Q) log(N))$
+
+use super::union_find::RollbackUnionFind;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Query {
+    /// 辺 (u, v) を追加する（多重辺も可）
+    Add(usize, usize),
+    /// 辺 (u, v) を1本削除する。存在しない辺を削除してはいけない
+    Remove(usize, usize),
+    /// u と v が連結か
+    Same(usize, usize),
+}
+
+/// n 頂点のグラフに `queries` を順に適用し、`Query::Same` への答えを順に返す
+pub fn offline_dynamic_connectivity(n: usize, queries: &[Query]) -> Vec<bool> {
+    let t = queries.len();
+    if t == 0 {
+        return Vec::new();
+    }
+    let size = t.next_power_of_two();
+    let mut edges = vec![Vec::new(); 2 * size];
+    let mut add_edge = |l: usize, r: usize, e: (usize, usize)| {
+        let mut l = l + size;
+        let mut r = r + size;
+        while l < r {
+            if l & 1 == 1 {
+                edges[l].push(e);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                edges[r].push(e);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+    };
+
+    // 辺ごとに、まだ削除されていない追加時刻を積んでおく
+    let mut alive: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (i, q) in queries.iter().enumerate() {
+        match *q {
+            Query::Add(u, v) => {
+                alive.entry((u.min(v), u.max(v))).or_default().push(i);
+            }
+            Query::Remove(u, v) => {
+                let e = (u.min(v), u.max(v));
+                let s = alive
+                    .get_mut(&e)
+                    .and_then(|s| s.pop())
+                    .expect("removed an edge that does not exist");
+                add_edge(s, i, e);
+            }
+            Query::Same(..) => {}
+        }
+    }
+    for (e, starts) in alive {
+        for s in starts {
+            add_edge(s, t, e);
+        }
+    }
+
+    let mut uf = RollbackUnionFind::new(n);
+    let mut res = Vec::new();
+    // (ノード, 入るときか)
+    let mut stack = vec![(1, true)];
+    let mut snapshots = Vec::new();
+    while let Some((k, enter)) = stack.pop() {
+        if !enter {
+            uf.rollback(snapshots.pop().unwrap());
+            continue;
+        }
+        if k >= size && k - size >= t {
+            continue;
+        }
+        snapshots.push(uf.snapshot());
+        for &(u, v) in edges[k].iter() {
+            uf.merge(u, v);
+        }
+        stack.push((k, false));
+        if k >= size {
+            if let Query::Same(u, v) = queries[k - size] {
+                res.push(uf.same(u, v));
+            }
+        } else {
+            stack.push((2 * k + 1, true));
+            stack.push((2 * k, true));
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::union_find::UnionFind;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn dynamic_connectivity_test() {
+        let queries = [
+            Query::Add(0, 1),
+            Query::Add(1, 2),
+            Query::Same(0, 2),
+            Query::Remove(2, 1),
+            Query::Same(0, 2),
+            Query::Same(0, 1),
+            Query::Add(0, 2),
+            Query::Same(1, 2),
+        ];
+        assert_eq!(
+            offline_dynamic_connectivity(3, &queries),
+            vec![true, false, true, true]
+        );
+    }
+
+    #[test]
+    fn dynamic_connectivity_random_test() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let n = 8;
+        let mut queries = Vec::new();
+        let mut edges: Vec<(usize, usize)> = Vec::new();
+        let mut expected = Vec::new();
+        for _ in 0..300 {
+            let (u, v) = (rng.random_range(0..n), rng.random_range(0..n));
+            match rng.random_range(0..3) {
+                0 => {
+                    queries.push(Query::Add(u, v));
+                    edges.push((u, v));
+                }
+                1 if !edges.is_empty() => {
+                    let (a, b) = edges.swap_remove(rng.random_range(0..edges.len()));
+                    queries.push(Query::Remove(b, a));
+                }
+                _ => {
+                    queries.push(Query::Same(u, v));
+                    let mut uf = UnionFind::new(n);
+                    for &(a, b) in edges.iter() {
+                        uf.merge(a, b);
+                    }
+                    expected.push(uf.same(u, v));
+                }
+            }
+        }
+        assert_eq!(offline_dynamic_connectivity(n, &queries), expected);
+    }
+}
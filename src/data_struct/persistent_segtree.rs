@@ -0,0 +1,290 @@
+//! 永続セグメント木
+//!
+//! 更新のたびに新しいバージョンを作り、過去のどのバージョンに対しても
+//! 一点更新・区間積を$O(log(N))$で行える。1回の更新で増えるノードは$O(log(N))$個
+//!
+//! 群の場合は2つのバージョンの差に対する二分探索（`max_right_diff`）ができる。
+//! 値の出現回数を持つ木を列の接頭辞ごとに作っておけば、区間の k 番目に小さい値などが求まる
+//!
+//! 区間の指定は `Segtree::prod` と同じく `RangeBounds` で行う
+
+use super::algebra::{Group, Monoid};
+use super::bit::range_to_pair;
+use std::ops::RangeBounds;
+
+/// `PersistentSegtree` のバージョン
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Version(usize);
+
+#[derive(Clone)]
+struct Node<S> {
+    left: usize,
+    right: usize,
+    val: S,
+}
+
+pub struct PersistentSegtree<M: Monoid> {
+    n: usize,
+    // 全バージョンのノードを置くアリーナ
+    nodes: Vec<Node<M::S>>,
+    initial: Version,
+}
+
+impl<M: Monoid> Clone for PersistentSegtree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            nodes: self.nodes.clone(),
+            initial: self.initial,
+        }
+    }
+}
+
+impl<M: Monoid> From<Vec<M::S>> for PersistentSegtree<M> {
+    fn from(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let mut seg = Self {
+            n,
+            nodes: Vec::with_capacity(2 * n.max(1)),
+            initial: Version(0),
+        };
+        let root = if n == 0 {
+            seg.push(0, 0, M::identity())
+        } else {
+            seg.build(&v, 0, n)
+        };
+        seg.initial = Version(root);
+        seg
+    }
+}
+
+impl<M: Monoid> PersistentSegtree<M> {
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 初期状態のバージョン
+    pub fn initial(&self) -> Version {
+        self.initial
+    }
+
+    /// バージョン v の $a_p$ を x に更新したバージョンを返す
+    pub fn set(&mut self, v: Version, p: usize, x: M::S) -> Version {
+        assert!(p < self.n);
+        Version(self.modify(v.0, 0, self.n, p, &mut |_| x.clone()))
+    }
+
+    /// バージョン v の $a_p$ を $a_p \cdot x$ に更新したバージョンを返す
+    pub fn update(&mut self, v: Version, p: usize, x: M::S) -> Version {
+        assert!(p < self.n);
+        Version(self.modify(v.0, 0, self.n, p, &mut |a| M::op(a, &x)))
+    }
+
+    /// バージョン v の $a_p$
+    pub fn get(&self, v: Version, p: usize) -> M::S {
+        assert!(p < self.n);
+        let (mut k, mut lo, mut hi) = (v.0, 0, self.n);
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if p < mid {
+                k = self.nodes[k].left;
+                hi = mid;
+            } else {
+                k = self.nodes[k].right;
+                lo = mid;
+            }
+        }
+        self.nodes[k].val.clone()
+    }
+
+    /// バージョン v の区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&self, v: Version, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        self.prod_rec(v.0, 0, self.n, l, r)
+    }
+
+    fn push(&mut self, left: usize, right: usize, val: M::S) -> usize {
+        self.nodes.push(Node { left, right, val });
+        self.nodes.len() - 1
+    }
+
+    fn build(&mut self, v: &[M::S], lo: usize, hi: usize) -> usize {
+        if hi - lo == 1 {
+            return self.push(0, 0, v[lo].clone());
+        }
+        let mid = (lo + hi) / 2;
+        let left = self.build(v, lo, mid);
+        let right = self.build(v, mid, hi);
+        let val = M::op(&self.nodes[left].val, &self.nodes[right].val);
+        self.push(left, right, val)
+    }
+
+    fn modify(
+        &mut self,
+        k: usize,
+        lo: usize,
+        hi: usize,
+        p: usize,
+        f: &mut dyn FnMut(&M::S) -> M::S,
+    ) -> usize {
+        if hi - lo == 1 {
+            let val = f(&self.nodes[k].val);
+            return self.push(0, 0, val);
+        }
+        let mid = (lo + hi) / 2;
+        let (mut left, mut right) = (self.nodes[k].left, self.nodes[k].right);
+        if p < mid {
+            left = self.modify(left, lo, mid, p, f);
+        } else {
+            right = self.modify(right, mid, hi, p, f);
+        }
+        let val = M::op(&self.nodes[left].val, &self.nodes[right].val);
+        self.push(left, right, val)
+    }
+
+    fn prod_rec(&self, k: usize, lo: usize, hi: usize, l: usize, r: usize) -> M::S {
+        if l <= lo && hi <= r {
+            return self.nodes[k].val.clone();
+        }
+        let mid = (lo + hi) / 2;
+        let node = &self.nodes[k];
+        if r <= mid {
+            self.prod_rec(node.left, lo, mid, l, r)
+        } else if mid <= l {
+            self.prod_rec(node.right, mid, hi, l, r)
+        } else {
+            M::op(
+                &self.prod_rec(node.left, lo, mid, l, r),
+                &self.prod_rec(node.right, mid, hi, l, r),
+            )
+        }
+    }
+}
+
+impl<G: Group> PersistentSegtree<G> {
+    /// バージョン hi からバージョン lo を引いた列 $b$ に対して、
+    /// `f(`$b_0 \cdot \cdots \cdot b_{r-1}$`)` が `true` となる最大の r を返す
+    ///
+    /// `f` は単調で、`f(`単位元`)` は `true` であること。群は可換であること
+    pub fn max_right_diff<F: Fn(&G::S) -> bool>(&self, lo: Version, hi: Version, f: F) -> usize {
+        assert!(f(&G::identity()));
+        let diff = |a: usize, b: usize| G::op(&G::inverse(&self.nodes[a].val), &self.nodes[b].val);
+        let (mut a, mut b) = (lo.0, hi.0);
+        let mut acc = G::identity();
+        if self.n == 0 || f(&G::op(&acc, &diff(a, b))) {
+            return self.n;
+        }
+        let (mut l, mut r) = (0, self.n);
+        while r - l > 1 {
+            let mid = (l + r) / 2;
+            let (la, lb) = (self.nodes[a].left, self.nodes[b].left);
+            let t = G::op(&acc, &diff(la, lb));
+            if f(&t) {
+                acc = t;
+                a = self.nodes[a].right;
+                b = self.nodes[b].right;
+                l = mid;
+            } else {
+                a = la;
+                b = lb;
+                r = mid;
+            }
+        }
+        l
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::{Additive, Affine};
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn persistent_segtree_test() {
+        type Mint = ModInt998244353;
+        let mut rng = StdRng::seed_from_u64(24);
+        for n in 0..20 {
+            let a: Vec<(Mint, Mint)> = (0..n)
+                .map(|_| {
+                    (
+                        Mint::new(rng.random_range(0..10)),
+                        Mint::new(rng.random_range(0..10)),
+                    )
+                })
+                .collect();
+            let mut seg = PersistentSegtree::<Affine<Mint>>::from(a.clone());
+            let mut versions = vec![seg.initial()];
+            let mut naive = vec![a];
+            for _ in 0..100 {
+                let from = rng.random_range(0..versions.len());
+                let mut b = naive[from].clone();
+                if n > 0 {
+                    let p = rng.random_range(0..n);
+                    let x = (
+                        Mint::new(rng.random_range(0..10)),
+                        Mint::new(rng.random_range(0..10)),
+                    );
+                    let v = if rng.random_bool(0.5) {
+                        b[p] = x;
+                        seg.set(versions[from], p, x)
+                    } else {
+                        b[p] = Affine::<Mint>::op(&b[p], &x);
+                        seg.update(versions[from], p, x)
+                    };
+                    versions.push(v);
+                    naive.push(b);
+                }
+                let k = rng.random_range(0..versions.len());
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let expected = naive[k][l..r]
+                    .iter()
+                    .fold(Affine::<Mint>::identity(), |f, g| Affine::<Mint>::op(&f, g));
+                assert_eq!(seg.prod(versions[k], l..r), expected);
+                for (i, x) in naive[k].iter().enumerate() {
+                    assert_eq!(seg.get(versions[k], i), *x);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_kth_smallest_test() {
+        let mut rng = StdRng::seed_from_u64(25);
+        let n = 50;
+        let a: Vec<usize> = (0..n).map(|_| rng.random_range(0..20)).collect();
+        // versions[i] は a[..i] の値ごとの出現回数
+        let mut seg = PersistentSegtree::<Additive<usize>>::new(20);
+        let mut versions = vec![seg.initial()];
+        for &x in a.iter() {
+            let v = seg.update(*versions.last().unwrap(), x, 1);
+            versions.push(v);
+        }
+        for l in 0..=n {
+            for r in l..=n {
+                let mut sorted = a[l..r].to_vec();
+                sorted.sort_unstable();
+                for k in 0..=r - l {
+                    let x = seg.max_right_diff(versions[l], versions[r], |&c| c <= k);
+                    assert_eq!(x, sorted.get(k).copied().unwrap_or(20));
+                }
+                assert_eq!(seg.prod(versions[r], ..) - seg.prod(versions[l], ..), r - l);
+            }
+        }
+    }
+}
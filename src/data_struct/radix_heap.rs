@@ -0,0 +1,133 @@
+//! Radix Heap
+//!
+//! 符号なし整数をキーとする最小ヒープ。最後に取り出したキー以上のキーしか追加できない代わりに、
+//! 追加は$O(1)$、取り出しは償却$O(log(C))$（C はキーの最大値）
+//!
+//! 辺の重みが非負整数の Dijkstra 法で `BinaryHeap<Reverse<_>>` の代わりに使える
+
+/// `RadixHeap` のキーに使える符号なし整数
+pub trait RadixKey: Copy + Ord + Default {
+    const BITS: u32;
+    /// 2つのキーの排他的論理和のビット長
+    fn xor_bit_len(self, other: Self) -> usize;
+}
+
+macro_rules! impl_radix_key {
+    ($($t: ty),*) => {$(
+        impl RadixKey for $t {
+            const BITS: u32 = <$t>::BITS;
+            fn xor_bit_len(self, other: Self) -> usize {
+                (<$t>::BITS - (self ^ other).leading_zeros()) as usize
+            }
+        }
+    )*};
+}
+impl_radix_key!(u8, u16, u32, u64, u128, usize);
+
+#[derive(Clone, Debug)]
+pub struct RadixHeap<K: RadixKey, V> {
+    len: usize,
+    // 最後に取り出したキー
+    last: K,
+    // buckets[i] には last との排他的論理和のビット長が i の要素を置く
+    buckets: Vec<Vec<(K, V)>>,
+}
+
+impl<K: RadixKey, V> Default for RadixHeap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: RadixKey, V> RadixHeap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            len: 0,
+            last: K::default(),
+            buckets: (0..=K::BITS).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 要素を追加する。key は最後に取り出したキー以上であること
+    pub fn push(&mut self, key: K, val: V) {
+        assert!(
+            self.last <= key,
+            "key must not be less than the last popped key"
+        );
+        self.buckets[key.xor_bit_len(self.last)].push((key, val));
+        self.len += 1;
+    }
+
+    /// キーが最小の要素を取り出す
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        if self.len == 0 {
+            return None;
+        }
+        if self.buckets[0].is_empty() {
+            let i = self.buckets.iter().position(|b| !b.is_empty()).unwrap();
+            let items = std::mem::take(&mut self.buckets[i]);
+            self.last = items.iter().map(|&(k, _)| k).min().unwrap();
+            for (k, v) in items {
+                self.buckets[k.xor_bit_len(self.last)].push((k, v));
+            }
+        }
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    #[test]
+    fn radix_heap_test() {
+        let mut rng = StdRng::seed_from_u64(32);
+        let mut heap = RadixHeap::<u64, usize>::new();
+        let mut naive = BinaryHeap::new();
+        let mut last = 0;
+        for id in 0..5000 {
+            if rng.random_bool(0.6) {
+                let bits = rng.random_range(0..40);
+                let key = last + rng.random_range(0..1u64 << bits);
+                heap.push(key, id);
+                naive.push(Reverse((key, id)));
+            } else {
+                let expected = naive.pop().map(|Reverse(x)| x);
+                let got = heap.pop();
+                assert_eq!(got.map(|x| x.0), expected.map(|x| x.0));
+                if let Some((key, _)) = got {
+                    last = key;
+                }
+            }
+            assert_eq!(heap.len(), naive.len());
+        }
+        let mut heap = RadixHeap::<u8, char>::new();
+        heap.push(255, 'b');
+        heap.push(0, 'a');
+        assert_eq!(heap.pop(), Some((0, 'a')));
+        assert_eq!(heap.pop(), Some((255, 'b')));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_heap_non_monotone_test() {
+        let mut heap = RadixHeap::<u32, ()>::new();
+        heap.push(5, ());
+        heap.pop();
+        heap.push(4, ());
+    }
+}
@@ -0,0 +1,184 @@
+//! BIT による区間加算
+//!
+//! - `RangeAddRangeSumBIT`: 区間 $[l, r)$ への加算と区間和を$O(log(N))$で行う（BIT を2本使う）
+//! - `RangeAddPointGetBIT`: 区間 $[l, r)$ への加算と一点取得を$O(log(N))$で行う（差分列を BIT に載せる）
+
+use super::algebra::{Additive, Group, Monoid};
+use super::bit::{range_to_pair, BIT};
+use std::convert::TryFrom;
+use std::ops::{Mul, RangeBounds};
+
+/// 区間加算・区間和
+///
+/// 区間 $[l, r)$ に x を加算すると、1本目の BIT に傾き、2本目の BIT に切片を持たせることで
+/// 先頭 p 項の和が `b1.sum(p) * p + b2.sum(p)` で求まる
+///
+/// 符号なし整数を載せる場合、負の値の加算はできない
+pub struct RangeAddRangeSumBIT<T>
+where
+    Additive<T>: Group<S = T>,
+{
+    n: usize,
+    b1: BIT<Additive<T>>,
+    b2: BIT<Additive<T>>,
+}
+
+impl<T> RangeAddRangeSumBIT<T>
+where
+    Additive<T>: Group<S = T>,
+    T: Clone + Mul<Output = T> + TryFrom<usize>,
+{
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            b1: BIT::new(n),
+            b2: BIT::new(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 区間 `range` の各要素に x を加算する
+    pub fn add<R: RangeBounds<usize>>(&mut self, range: R, x: T) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return;
+        }
+        let xl = x.clone() * Self::cast(l);
+        self.b1.add(l + 1, x.clone());
+        self.b2.add(l + 1, Additive::<T>::inverse(&xl));
+        if r < self.n {
+            let xr = x.clone() * Self::cast(r);
+            self.b1.add(r + 1, Additive::<T>::inverse(&x));
+            self.b2.add(r + 1, xr);
+        }
+    }
+
+    /// $a_0 + \cdots + a_{r-1}$ を求める
+    pub fn sum(&self, r: usize) -> T {
+        Additive::<T>::op(&(self.b1.sum(r) * Self::cast(r)), &self.b2.sum(r))
+    }
+
+    /// 区間 `range` の総和を求める
+    pub fn sum_range<R: RangeBounds<usize>>(&self, range: R) -> T {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        Additive::<T>::op(&self.sum(r), &Additive::<T>::inverse(&self.sum(l)))
+    }
+
+    fn cast(i: usize) -> T {
+        T::try_from(i).ok().expect("index does not fit in T")
+    }
+}
+
+/// 区間加算・一点取得
+pub struct RangeAddPointGetBIT<G: Group> {
+    n: usize,
+    bit: BIT<G>,
+}
+
+impl<G: Group> RangeAddPointGetBIT<G> {
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            bit: BIT::new(n),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 区間 `range` の各要素に x を加算する
+    pub fn add<R: RangeBounds<usize>>(&mut self, range: R, x: G::S) {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return;
+        }
+        if r < self.n {
+            self.bit.add(r + 1, G::inverse(&x));
+        }
+        self.bit.add(l + 1, x);
+    }
+
+    /// $a_i$ を求める
+    pub fn get(&self, i: usize) -> G::S {
+        assert!(i < self.n);
+        self.bit.sum(i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn range_add_range_sum_test() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let n = 20;
+        let mut bit = RangeAddRangeSumBIT::<i64>::new(n);
+        let mut naive = vec![0i64; n];
+        for _ in 0..1000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            if rng.random_bool(0.5) {
+                let x = rng.random_range(-100..=100);
+                bit.add(l..r, x);
+                naive[l..r].iter_mut().for_each(|a| *a += x);
+            } else {
+                assert_eq!(bit.sum_range(l..r), naive[l..r].iter().sum::<i64>());
+            }
+        }
+    }
+
+    #[test]
+    fn range_add_range_sum_unsigned_test() {
+        let mut bit = RangeAddRangeSumBIT::<usize>::new(5);
+        bit.add(1..4, 3);
+        bit.add(..2, 1);
+        assert_eq!(bit.sum_range(..), 11);
+        assert_eq!(bit.sum_range(1..=1), 4);
+        assert_eq!(bit.sum_range(3..), 3);
+    }
+
+    #[test]
+    fn range_add_range_sum_modint_test() {
+        type Mint = ModInt998244353;
+        let mut bit = RangeAddRangeSumBIT::<Mint>::new(4);
+        bit.add(.., Mint::new(998_244_352));
+        bit.add(2.., Mint::new(3));
+        assert_eq!(bit.sum_range(..).val(), 2);
+    }
+
+    #[test]
+    fn range_add_point_get_test() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let n = 20;
+        let mut bit = RangeAddPointGetBIT::<Additive<i64>>::new(n);
+        let mut naive = vec![0i64; n];
+        for _ in 0..1000 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let x = rng.random_range(-100..=100);
+            bit.add(l..r, x);
+            naive[l..r].iter_mut().for_each(|a| *a += x);
+            let i = rng.random_range(0..n);
+            assert_eq!(bit.get(i), naive[i]);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! セグメント木
+//!
+//! モノイド `M` の元の列 $a_0, a_1, \cdots , a_{n-1}$ に対して以下の様なことができる
+//! - 一点更新
+//! - 区間積 $a_l \cdot a_{l+1} \cdot \cdots \cdot a_{r-1}$ の取得
+//! - 区間積に対する単調な条件での二分探索（`max_right`, `min_left`）
+//! - 計算量はいずれも$O(log(N))$
+//!
+//! 区間の指定は `RangeBounds` で行う（`l..r` は $a_l, \cdots , a_{r-1}$）
+
+use super::algebra::Monoid;
+use super::bit::range_to_pair;
+use std::ops::RangeBounds;
+
+pub struct Segtree<M: Monoid> {
+    n: usize,
+    size: usize,
+    log: usize,
+    d: Vec<M::S>,
+}
+
+impl<M: Monoid> Clone for Segtree<M> {
+    fn clone(&self) -> Self {
+        Self {
+            n: self.n,
+            size: self.size,
+            log: self.log,
+            d: self.d.clone(),
+        }
+    }
+}
+
+impl<M: Monoid> From<Vec<M::S>> for Segtree<M> {
+    fn from(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        let mut d = vec![M::identity(); 2 * size];
+        for (i, x) in v.into_iter().enumerate() {
+            d[size + i] = x;
+        }
+        let mut seg = Self { n, size, log, d };
+        for i in (1..size).rev() {
+            seg.update(i);
+        }
+        seg
+    }
+}
+
+impl<M: Monoid> Segtree<M> {
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_p$ を x に更新する
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        self.d[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    pub fn get(&self, p: usize) -> M::S {
+        assert!(p < self.n);
+        self.d[p + self.size].clone()
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        let mut sml = M::identity();
+        let mut smr = M::identity();
+        let mut l = l + self.size;
+        let mut r = r + self.size;
+        while l < r {
+            if l & 1 == 1 {
+                sml = M::op(&sml, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = M::op(&self.d[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::op(&sml, &smr)
+    }
+
+    pub fn all_prod(&self) -> M::S {
+        self.d[1].clone()
+    }
+
+    /// `f(&self.prod(l..r))` が true となる最大の r を求める
+    ///
+    /// f は単調で、`f(&M::identity())` は true であること
+    pub fn max_right<F: Fn(&M::S) -> bool>(&self, l: usize, f: F) -> usize {
+        assert!(l <= self.n);
+        assert!(f(&M::identity()));
+        if l == self.n {
+            return self.n;
+        }
+        let mut l = l + self.size;
+        let mut sm = M::identity();
+        loop {
+            while l & 1 == 0 {
+                l >>= 1;
+            }
+            if !f(&M::op(&sm, &self.d[l])) {
+                while l < self.size {
+                    l *= 2;
+                    let res = M::op(&sm, &self.d[l]);
+                    if f(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = M::op(&sm, &self.d[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                break;
+            }
+        }
+        self.n
+    }
+
+    /// `f(&self.prod(l..r))` が true となる最小の l を求める
+    ///
+    /// f は単調で、`f(&M::identity())` は true であること
+    pub fn min_left<F: Fn(&M::S) -> bool>(&self, r: usize, f: F) -> usize {
+        assert!(r <= self.n);
+        assert!(f(&M::identity()));
+        if r == 0 {
+            return 0;
+        }
+        let mut r = r + self.size;
+        let mut sm = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r & 1 == 1 {
+                r >>= 1;
+            }
+            if !f(&M::op(&self.d[r], &sm)) {
+                while r < self.size {
+                    r = 2 * r + 1;
+                    let res = M::op(&self.d[r], &sm);
+                    if f(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = M::op(&self.d[r], &sm);
+            if r & r.wrapping_neg() == r {
+                break;
+            }
+        }
+        0
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = M::op(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::{Additive, Affine, Gcd, Max, Min};
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn segtree_random_test() {
+        let mut rng = StdRng::seed_from_u64(6);
+        for n in 0..20 {
+            let mut naive: Vec<i64> = (0..n).map(|_| rng.random_range(-100..=100)).collect();
+            let mut seg = Segtree::<Max<i64>>::from(naive.clone());
+            for _ in 0..200 {
+                if n > 0 {
+                    let p = rng.random_range(0..n);
+                    let x = rng.random_range(-100..=100);
+                    seg.set(p, x);
+                    naive[p] = x;
+                    assert_eq!(seg.get(p), x);
+                }
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let max =
+                    |l: usize, r: usize| naive[l..r].iter().copied().max().unwrap_or(i64::MIN);
+                assert_eq!(seg.prod(l..r), max(l, r));
+
+                let bound = rng.random_range(-100..=100);
+                let expected = (l..=n).rev().find(|&r| max(l, r) <= bound).unwrap();
+                assert_eq!(seg.max_right(l, |&x| x <= bound), expected);
+                let expected = (0..=r).find(|&l| max(l, r) <= bound).unwrap();
+                assert_eq!(seg.min_left(r, |&x| x <= bound), expected);
+            }
+            assert_eq!(seg.all_prod(), seg.prod(..));
+        }
+    }
+
+    #[test]
+    fn segtree_monoids_test() {
+        let seg = Segtree::<Min<u32>>::from(vec![5, 3, 8, 1]);
+        assert_eq!(seg.prod(..3), 3);
+        assert_eq!(seg.prod(4..), u32::MAX);
+
+        let seg = Segtree::<Additive<i64>>::from(vec![5, -3, 8, 1]);
+        assert_eq!(seg.prod(1..=2), 5);
+
+        let seg = Segtree::<Gcd<i64>>::from(vec![12, 18, -30, 7]);
+        assert_eq!(seg.prod(..3), 6);
+        assert_eq!(seg.prod(..), 1);
+
+        type Mint = ModInt998244353;
+        let f = |a: u64, b: u64| (Mint::new(a), Mint::new(b));
+        let seg = Segtree::<Affine<Mint>>::from(vec![f(2, 1), f(3, 4), f(1, 5)]);
+        // x -> 2x + 1 -> 3(2x + 1) + 4 = 6x + 7
+        assert_eq!(seg.prod(..2), f(6, 7));
+        assert_eq!(seg.prod(1..), f(3, 9));
+        assert_eq!(seg.all_prod(), f(6, 12));
+    }
+}
@@ -0,0 +1,374 @@
+//! Segment Tree Beats
+//!
+//! 整数列 $a_0, a_1, \cdots , a_{n-1}$ に対して以下の様なことができる
+//! - 区間 chmin: $a_i \leftarrow \min(a_i, x)$
+//! - 区間 chmax: $a_i \leftarrow \max(a_i, x)$
+//! - 区間加算・区間代入
+//! - 区間和・区間最小値・区間最大値の取得
+//! - 計算量は償却$O(log(N)^2)$（加算がなければ$O(log(N))$）
+//!
+//! 各ノードで最大値・2番目の最大値・最大値の個数（最小値も同様）を持ち、
+//! 更新値が2番目の値より真に大きい（小さい）ところまでだけ降りていく
+//!
+//! 区間の指定は `Segtree::prod` と同じく `RangeBounds` で行う
+
+use super::bit::range_to_pair;
+use std::cmp::Ordering;
+use std::ops::RangeBounds;
+
+const INF: i64 = i64::MAX;
+const NEG_INF: i64 = i64::MIN;
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    sum: i64,
+    max1: i64,
+    max2: i64,
+    max_count: i64,
+    min1: i64,
+    min2: i64,
+    min_count: i64,
+    len: i64,
+    lazy_add: i64,
+}
+
+impl Node {
+    fn leaf(x: i64) -> Self {
+        Self {
+            sum: x,
+            max1: x,
+            max2: NEG_INF,
+            max_count: 1,
+            min1: x,
+            min2: INF,
+            min_count: 1,
+            len: 1,
+            lazy_add: 0,
+        }
+    }
+
+    fn merge(l: &Self, r: &Self) -> Self {
+        let (max1, max2, max_count) = match l.max1.cmp(&r.max1) {
+            Ordering::Equal => (l.max1, l.max2.max(r.max2), l.max_count + r.max_count),
+            Ordering::Greater => (l.max1, l.max2.max(r.max1), l.max_count),
+            Ordering::Less => (r.max1, r.max2.max(l.max1), r.max_count),
+        };
+        let (min1, min2, min_count) = match l.min1.cmp(&r.min1) {
+            Ordering::Equal => (l.min1, l.min2.min(r.min2), l.min_count + r.min_count),
+            Ordering::Less => (l.min1, l.min2.min(r.min1), l.min_count),
+            Ordering::Greater => (r.min1, r.min2.min(l.min1), r.min_count),
+        };
+        Self {
+            sum: l.sum + r.sum,
+            max1,
+            max2,
+            max_count,
+            min1,
+            min2,
+            min_count,
+            len: l.len + r.len,
+            lazy_add: 0,
+        }
+    }
+
+    /// 最大値だけを x に下げる（2番目の最大値 < x < 最大値）
+    fn apply_chmin(&mut self, x: i64) {
+        self.sum -= (self.max1 - x) * self.max_count;
+        if self.min1 == self.max1 {
+            self.min1 = x;
+        } else if self.min2 == self.max1 {
+            self.min2 = x;
+        }
+        self.max1 = x;
+    }
+
+    /// 最小値だけを x に上げる（最小値 < x < 2番目の最小値）
+    fn apply_chmax(&mut self, x: i64) {
+        self.sum += (x - self.min1) * self.min_count;
+        if self.max1 == self.min1 {
+            self.max1 = x;
+        } else if self.max2 == self.min1 {
+            self.max2 = x;
+        }
+        self.min1 = x;
+    }
+
+    fn apply_add(&mut self, x: i64) {
+        self.sum += x * self.len;
+        self.max1 += x;
+        if self.max2 != NEG_INF {
+            self.max2 += x;
+        }
+        self.min1 += x;
+        if self.min2 != INF {
+            self.min2 += x;
+        }
+        self.lazy_add += x;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SegtreeBeats {
+    n: usize,
+    d: Vec<Node>,
+}
+
+impl From<Vec<i64>> for SegtreeBeats {
+    fn from(v: Vec<i64>) -> Self {
+        let n = v.len();
+        let mut seg = Self {
+            n,
+            d: vec![Node::leaf(0); 4 * n.max(1)],
+        };
+        if n > 0 {
+            seg.build(&v, 1, 0, n);
+        }
+        seg
+    }
+}
+
+impl SegtreeBeats {
+    pub fn new(n: usize) -> Self {
+        vec![0; n].into()
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 区間 `range` の各要素を $\min(a_i, x)$ に更新する
+    pub fn chmin<R: RangeBounds<usize>>(&mut self, range: R, x: i64) {
+        if let Some((l, r)) = self.to_pair(range) {
+            self.chmin_rec(1, 0, self.n, l, r, x);
+        }
+    }
+
+    /// 区間 `range` の各要素を $\max(a_i, x)$ に更新する
+    pub fn chmax<R: RangeBounds<usize>>(&mut self, range: R, x: i64) {
+        if let Some((l, r)) = self.to_pair(range) {
+            self.chmax_rec(1, 0, self.n, l, r, x);
+        }
+    }
+
+    /// 区間 `range` の各要素に x を加える
+    pub fn add<R: RangeBounds<usize>>(&mut self, range: R, x: i64) {
+        if let Some((l, r)) = self.to_pair(range) {
+            self.add_rec(1, 0, self.n, l, r, x);
+        }
+    }
+
+    /// 区間 `range` の各要素を x にする
+    pub fn assign<R: RangeBounds<usize>>(&mut self, range: R, x: i64) {
+        if let Some((l, r)) = self.to_pair(range) {
+            self.chmin_rec(1, 0, self.n, l, r, x);
+            self.chmax_rec(1, 0, self.n, l, r, x);
+        }
+    }
+
+    pub fn get(&mut self, p: usize) -> i64 {
+        assert!(p < self.n);
+        self.sum(p..=p)
+    }
+
+    /// 区間 `range` の総和
+    pub fn sum<R: RangeBounds<usize>>(&mut self, range: R) -> i64 {
+        match self.to_pair(range) {
+            Some((l, r)) => self.fold(1, 0, self.n, l, r, 0, &|node| node.sum, &|a, b| a + b),
+            None => 0,
+        }
+    }
+
+    /// 区間 `range` の最小値（空なら `i64::MAX`）
+    pub fn min<R: RangeBounds<usize>>(&mut self, range: R) -> i64 {
+        match self.to_pair(range) {
+            Some((l, r)) => self.fold(1, 0, self.n, l, r, INF, &|node| node.min1, &i64::min),
+            None => INF,
+        }
+    }
+
+    /// 区間 `range` の最大値（空なら `i64::MIN`）
+    pub fn max<R: RangeBounds<usize>>(&mut self, range: R) -> i64 {
+        match self.to_pair(range) {
+            Some((l, r)) => self.fold(1, 0, self.n, l, r, NEG_INF, &|node| node.max1, &i64::max),
+            None => NEG_INF,
+        }
+    }
+
+    /// 空でない区間なら `(l, r)` を返す
+    fn to_pair<R: RangeBounds<usize>>(&self, range: R) -> Option<(usize, usize)> {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l < r {
+            Some((l, r))
+        } else {
+            None
+        }
+    }
+
+    fn build(&mut self, v: &[i64], k: usize, lo: usize, hi: usize) {
+        if hi - lo == 1 {
+            self.d[k] = Node::leaf(v[lo]);
+            return;
+        }
+        let mid = (lo + hi) / 2;
+        self.build(v, 2 * k, lo, mid);
+        self.build(v, 2 * k + 1, mid, hi);
+        self.update(k);
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = Node::merge(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+
+    fn push(&mut self, k: usize) {
+        let node = self.d[k];
+        for c in [2 * k, 2 * k + 1].iter().copied() {
+            let child = &mut self.d[c];
+            if node.lazy_add != 0 {
+                child.apply_add(node.lazy_add);
+            }
+            if child.max1 > node.max1 {
+                child.apply_chmin(node.max1);
+            }
+            if child.min1 < node.min1 {
+                child.apply_chmax(node.min1);
+            }
+        }
+        self.d[k].lazy_add = 0;
+    }
+
+    fn chmin_rec(&mut self, k: usize, lo: usize, hi: usize, l: usize, r: usize, x: i64) {
+        if r <= lo || hi <= l || self.d[k].max1 <= x {
+            return;
+        }
+        if l <= lo && hi <= r && self.d[k].max2 < x {
+            self.d[k].apply_chmin(x);
+            return;
+        }
+        self.push(k);
+        let mid = (lo + hi) / 2;
+        self.chmin_rec(2 * k, lo, mid, l, r, x);
+        self.chmin_rec(2 * k + 1, mid, hi, l, r, x);
+        self.update(k);
+    }
+
+    fn chmax_rec(&mut self, k: usize, lo: usize, hi: usize, l: usize, r: usize, x: i64) {
+        if r <= lo || hi <= l || self.d[k].min1 >= x {
+            return;
+        }
+        if l <= lo && hi <= r && self.d[k].min2 > x {
+            self.d[k].apply_chmax(x);
+            return;
+        }
+        self.push(k);
+        let mid = (lo + hi) / 2;
+        self.chmax_rec(2 * k, lo, mid, l, r, x);
+        self.chmax_rec(2 * k + 1, mid, hi, l, r, x);
+        self.update(k);
+    }
+
+    fn add_rec(&mut self, k: usize, lo: usize, hi: usize, l: usize, r: usize, x: i64) {
+        if r <= lo || hi <= l {
+            return;
+        }
+        if l <= lo && hi <= r {
+            self.d[k].apply_add(x);
+            return;
+        }
+        self.push(k);
+        let mid = (lo + hi) / 2;
+        self.add_rec(2 * k, lo, mid, l, r, x);
+        self.add_rec(2 * k + 1, mid, hi, l, r, x);
+        self.update(k);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn fold(
+        &mut self,
+        k: usize,
+        lo: usize,
+        hi: usize,
+        l: usize,
+        r: usize,
+        e: i64,
+        get: &dyn Fn(&Node) -> i64,
+        op: &dyn Fn(i64, i64) -> i64,
+    ) -> i64 {
+        if r <= lo || hi <= l {
+            return e;
+        }
+        if l <= lo && hi <= r {
+            return get(&self.d[k]);
+        }
+        self.push(k);
+        let mid = (lo + hi) / 2;
+        let a = self.fold(2 * k, lo, mid, l, r, e, get, op);
+        let b = self.fold(2 * k + 1, mid, hi, l, r, e, get, op);
+        op(a, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn segtree_beats_test() {
+        let mut rng = StdRng::seed_from_u64(29);
+        for n in 0..30 {
+            let mut naive: Vec<i64> = (0..n).map(|_| rng.random_range(-100..=100)).collect();
+            let mut seg = SegtreeBeats::from(naive.clone());
+            for _ in 0..300 {
+                let l = rng.random_range(0..=n);
+                let r = rng.random_range(l..=n);
+                let x = rng.random_range(-100..=100);
+                match rng.random_range(0..5) {
+                    0 => {
+                        seg.chmin(l..r, x);
+                        naive[l..r].iter_mut().for_each(|a| *a = (*a).min(x));
+                    }
+                    1 => {
+                        seg.chmax(l..r, x);
+                        naive[l..r].iter_mut().for_each(|a| *a = (*a).max(x));
+                    }
+                    2 => {
+                        seg.add(l..r, x);
+                        naive[l..r].iter_mut().for_each(|a| *a += x);
+                    }
+                    3 => {
+                        seg.assign(l..r, x);
+                        naive[l..r].iter_mut().for_each(|a| *a = x);
+                    }
+                    _ => {
+                        let a = &naive[l..r];
+                        assert_eq!(seg.sum(l..r), a.iter().sum::<i64>());
+                        assert_eq!(seg.min(l..r), a.iter().copied().min().unwrap_or(i64::MAX));
+                        assert_eq!(seg.max(l..r), a.iter().copied().max().unwrap_or(i64::MIN));
+                    }
+                }
+            }
+            for (i, &x) in naive.iter().enumerate() {
+                assert_eq!(seg.get(i), x);
+            }
+        }
+    }
+
+    #[test]
+    fn segtree_beats_full_range_test() {
+        let mut seg = SegtreeBeats::from(vec![5, 1, 4, 1, 5, 9, 2, 6]);
+        seg.chmin(.., 4);
+        assert_eq!(seg.sum(..), 4 + 1 + 4 + 1 + 4 + 4 + 2 + 4);
+        seg.chmax(..4, 3);
+        assert_eq!(seg.min(..), 2);
+        assert_eq!(seg.sum(..4), 4 + 3 + 4 + 3);
+        seg.add(2.., 10);
+        assert_eq!(seg.max(..), 14);
+        assert_eq!(seg.min(2..), 12);
+    }
+}
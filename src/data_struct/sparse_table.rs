@@ -0,0 +1,160 @@
+//! Sparse Table
+//!
+//! 静的な列に対する区間積を前計算$O(N log(N))$、クエリ$O(1)$で求める
+//! - `SparseTable`: 冪等なモノイド（min, max, gcd など）
+//! - `DisjointSparseTable`: 任意のモノイド（和、積、行列積など）
+//!
+//! 区間の指定は `Segtree::prod` と同じく `RangeBounds` で行う
+
+use super::algebra::{Idempotent, Monoid};
+use super::bit::range_to_pair;
+use std::ops::RangeBounds;
+
+pub struct SparseTable<M: Idempotent> {
+    n: usize,
+    // table[k][i] は区間 [i, i + 2^k) の積
+    table: Vec<Vec<M::S>>,
+}
+
+impl<M: Idempotent> SparseTable<M> {
+    pub fn new(a: &[M::S]) -> Self {
+        let n = a.len();
+        let mut table = vec![a.to_vec()];
+        let mut k = 1;
+        while 2 * k <= n {
+            let prev = table.last().unwrap();
+            let row = (0..=n - 2 * k)
+                .map(|i| M::op(&prev[i], &prev[i + k]))
+                .collect();
+            table.push(row);
+            k *= 2;
+        }
+        Self { n, table }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        let k = (usize::BITS - 1 - (r - l).leading_zeros()) as usize;
+        M::op(&self.table[k][l], &self.table[k][r - (1 << k)])
+    }
+}
+
+pub struct DisjointSparseTable<M: Monoid> {
+    n: usize,
+    // table[k] は長さ 2^k のブロックごとに、中央から左右へ伸ばした累積積
+    table: Vec<Vec<M::S>>,
+}
+
+impl<M: Monoid> DisjointSparseTable<M> {
+    pub fn new(a: &[M::S]) -> Self {
+        let n = a.len();
+        let mut table = vec![a.to_vec()];
+        let mut half = 1;
+        while half < n {
+            let mut row = a.to_vec();
+            for mid in (half..n).step_by(2 * half) {
+                for i in (mid - half..mid - 1).rev() {
+                    row[i] = M::op(&a[i], &row[i + 1]);
+                }
+                for i in mid + 1..(mid + half).min(n) {
+                    row[i] = M::op(&row[i - 1], &a[i]);
+                }
+            }
+            table.push(row);
+            half *= 2;
+        }
+        Self { n, table }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// 区間 `range` の総積を求める
+    pub fn prod<R: RangeBounds<usize>>(&self, range: R) -> M::S {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if l == r {
+            return M::identity();
+        }
+        let r = r - 1;
+        if l == r {
+            return self.table[0][l].clone();
+        }
+        let k = (usize::BITS - (l ^ r).leading_zeros()) as usize;
+        M::op(&self.table[k][l], &self.table[k][r])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::{Affine, Gcd, Min};
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn sparse_table_test() {
+        let mut rng = StdRng::seed_from_u64(13);
+        for n in 0..40 {
+            let a: Vec<i64> = (0..n).map(|_| rng.random_range(-100..=100)).collect();
+            let min = SparseTable::<Min<i64>>::new(&a);
+            let b: Vec<u64> = (0..n).map(|_| rng.random_range(1..=6) * 6).collect();
+            let gcd = SparseTable::<Gcd<u64>>::new(&b);
+            for l in 0..=n {
+                for r in l..=n {
+                    let expected = a[l..r].iter().copied().min().unwrap_or(i64::MAX);
+                    assert_eq!(min.prod(l..r), expected);
+                    let expected = b[l..r].iter().fold(0, |g, &x| num::integer::gcd(g, x));
+                    assert_eq!(gcd.prod(l..r), expected);
+                }
+            }
+        }
+        let min = SparseTable::<Min<i64>>::new(&[3, 1, 4, 1, 5]);
+        assert_eq!(min.prod(2..), 1);
+        assert_eq!(min.prod(..=0), 3);
+    }
+
+    #[test]
+    fn disjoint_sparse_table_test() {
+        type Mint = ModInt998244353;
+        let mut rng = StdRng::seed_from_u64(14);
+        for n in 0..40 {
+            let a: Vec<(Mint, Mint)> = (0..n)
+                .map(|_| {
+                    (
+                        Mint::new(rng.random_range(0..1000)),
+                        Mint::new(rng.random_range(0..1000)),
+                    )
+                })
+                .collect();
+            let table = DisjointSparseTable::<Affine<Mint>>::new(&a);
+            for l in 0..=n {
+                for r in l..=n {
+                    let expected = a[l..r]
+                        .iter()
+                        .fold(Affine::<Mint>::identity(), |f, g| Affine::<Mint>::op(&f, g));
+                    assert_eq!(table.prod(l..r), expected);
+                }
+            }
+        }
+    }
+}
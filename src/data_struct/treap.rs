@@ -0,0 +1,617 @@
+//! Treap（平衡二分探索木）
+//!
+//! - `OrderedMultiset`: 順序付き多重集合。挿入・削除・k 番目の要素・x 未満の要素数などを$O(log(N))$で行う
+//! - `ImplicitTreap`: 列。位置を指定した挿入・削除と区間の反転を$O(log(N))$で行う
+//!
+//! どちらも分割（`split_off`）と連結（`append`）が$O(log(N))$でできる。計算量はいずれも期待値
+
+use super::bit::range_to_pair;
+use std::cmp::Ordering;
+use std::mem;
+use std::ops::RangeBounds;
+
+type Tree<T> = Option<Box<Node<T>>>;
+
+struct Node<T> {
+    val: T,
+    priority: u64,
+    size: usize,
+    // 部分木を反転する遅延フラグ（自身の子にはまだ反映していない）
+    rev: bool,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+impl<T> Node<T> {
+    fn new(val: T, priority: u64) -> Box<Self> {
+        Box::new(Self {
+            val,
+            priority,
+            size: 1,
+            rev: false,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+
+    fn push(&mut self) {
+        if self.rev {
+            mem::swap(&mut self.left, &mut self.right);
+            if let Some(l) = self.left.as_mut() {
+                l.rev ^= true;
+            }
+            if let Some(r) = self.right.as_mut() {
+                r.rev ^= true;
+            }
+            self.rev = false;
+        }
+    }
+}
+
+fn size<T>(t: &Tree<T>) -> usize {
+    t.as_ref().map_or(0, |n| n.size)
+}
+
+fn merge<T>(a: Tree<T>, b: Tree<T>) -> Tree<T> {
+    match (a, b) {
+        (None, b) => b,
+        (a, None) => a,
+        (Some(mut a), Some(mut b)) => {
+            if a.priority > b.priority {
+                a.push();
+                a.right = merge(a.right.take(), Some(b));
+                a.update();
+                Some(a)
+            } else {
+                b.push();
+                b.left = merge(Some(a), b.left.take());
+                b.update();
+                Some(b)
+            }
+        }
+    }
+}
+
+/// 先頭 k 個とそれ以外に分割する
+fn split_at<T>(t: Tree<T>, k: usize) -> (Tree<T>, Tree<T>) {
+    match t {
+        None => (None, None),
+        Some(mut n) => {
+            n.push();
+            if size(&n.left) >= k {
+                let (l, r) = split_at(n.left.take(), k);
+                n.left = r;
+                n.update();
+                (l, Some(n))
+            } else {
+                let k = k - size(&n.left) - 1;
+                let (l, r) = split_at(n.right.take(), k);
+                n.right = l;
+                n.update();
+                (Some(n), r)
+            }
+        }
+    }
+}
+
+/// `go_left(&val)` が true となる要素とそれ以外に分割する（go_left は単調であること）
+fn split_by<T, F: Fn(&T) -> bool>(t: Tree<T>, go_left: &F) -> (Tree<T>, Tree<T>) {
+    match t {
+        None => (None, None),
+        Some(mut n) => {
+            n.push();
+            if go_left(&n.val) {
+                let (l, r) = split_by(n.right.take(), go_left);
+                n.right = l;
+                n.update();
+                (Some(n), r)
+            } else {
+                let (l, r) = split_by(n.left.take(), go_left);
+                n.left = r;
+                n.update();
+                (l, Some(n))
+            }
+        }
+    }
+}
+
+/// 遅延フラグを考慮して k 番目の要素を探す
+fn nth<T>(t: &Tree<T>, k: usize) -> Option<&T> {
+    let mut t = t;
+    let mut k = k;
+    let mut rev = false;
+    while let Some(n) = t {
+        rev ^= n.rev;
+        let (left, right) = if rev {
+            (&n.right, &n.left)
+        } else {
+            (&n.left, &n.right)
+        };
+        let ls = size(left);
+        match k.cmp(&ls) {
+            Ordering::Less => t = left,
+            Ordering::Equal => return Some(&n.val),
+            Ordering::Greater => {
+                k -= ls + 1;
+                t = right;
+            }
+        }
+    }
+    None
+}
+
+/// 先頭から順に要素を返すイテレータ
+pub struct Iter<'a, T> {
+    // (ノード, そのノードの子に適用すべき反転)
+    stack: Vec<(&'a Node<T>, bool)>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new(t: &'a Tree<T>) -> Self {
+        let mut iter = Self { stack: Vec::new() };
+        iter.push_left(t, false);
+        iter
+    }
+
+    fn push_left(&mut self, t: &'a Tree<T>, rev: bool) {
+        let mut t = t;
+        let mut rev = rev;
+        while let Some(n) = t {
+            rev ^= n.rev;
+            self.stack.push((n, rev));
+            t = if rev { &n.right } else { &n.left };
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<&'a T> {
+        let (n, rev) = self.stack.pop()?;
+        self.push_left(if rev { &n.left } else { &n.right }, rev);
+        Some(&n.val)
+    }
+}
+
+/// 優先度生成用の xorshift
+#[derive(Clone, Debug)]
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// 元とは相関しない乱数列を作る。状態を複製したり出力をそのまま種にしたりすると、
+    /// 同じ（またはずれただけの）優先度の列になり、併合後の木が偏る
+    fn fork(&mut self) -> Self {
+        // splitmix64 で混ぜる
+        let mut z = self.next().wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        XorShift((z ^ (z >> 31)).max(1))
+    }
+}
+
+impl Default for XorShift {
+    fn default() -> Self {
+        XorShift(88_172_645_463_325_252)
+    }
+}
+
+/// 順序付き多重集合
+pub struct OrderedMultiset<T: Ord> {
+    root: Tree<T>,
+    rng: XorShift,
+}
+
+impl<T: Ord> Default for OrderedMultiset<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> OrderedMultiset<T> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            rng: XorShift::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn insert(&mut self, x: T) {
+        let (l, r) = split_by(self.root.take(), &|v| *v < x);
+        let node = Some(Node::new(x, self.rng.next()));
+        self.root = merge(merge(l, node), r);
+    }
+
+    /// x が含まれていなければ挿入して true を返す（集合として使う場合）
+    pub fn insert_unique(&mut self, x: T) -> bool {
+        if self.contains(&x) {
+            return false;
+        }
+        self.insert(x);
+        true
+    }
+
+    /// x を1つ削除する。x が含まれていなければ false を返す
+    pub fn remove(&mut self, x: &T) -> bool {
+        let (l, r) = split_by(self.root.take(), &|v| v < x);
+        let (m, r) = split_by(r, &|v| v <= x);
+        let found = m.is_some();
+        let (_, m) = split_at(m, 1);
+        self.root = merge(merge(l, m), r);
+        found
+    }
+
+    pub fn contains(&self, x: &T) -> bool {
+        self.count(x) > 0
+    }
+
+    pub fn count(&self, x: &T) -> usize {
+        self.rank_upper(x) - self.rank(x)
+    }
+
+    /// 小さい方から k 番目（0-indexed）の要素
+    pub fn nth(&self, k: usize) -> Option<&T> {
+        nth(&self.root, k)
+    }
+
+    /// x 未満の要素数
+    pub fn rank(&self, x: &T) -> usize {
+        self.count_by(|v| v < x)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.nth(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|k| self.nth(k))
+    }
+
+    /// x 以上の最小の要素を指すカーソル
+    pub fn lower_bound(&self, x: &T) -> Cursor<'_, T> {
+        Cursor {
+            set: self,
+            index: self.rank(x),
+        }
+    }
+
+    /// x より大きい最小の要素を指すカーソル
+    pub fn upper_bound(&self, x: &T) -> Cursor<'_, T> {
+        Cursor {
+            set: self,
+            index: self.rank_upper(x),
+        }
+    }
+
+    /// x 以上の要素を切り離して返す
+    pub fn split_off(&mut self, x: &T) -> Self {
+        let (l, r) = split_by(self.root.take(), &|v| v < x);
+        self.root = l;
+        Self {
+            root: r,
+            rng: self.rng.fork(),
+        }
+    }
+
+    /// other の要素をすべて移す。self の要素はすべて other の要素以下であること
+    pub fn append(&mut self, other: &mut Self) {
+        debug_assert!(match (self.last(), other.first()) {
+            (Some(a), Some(b)) => a <= b,
+            _ => true,
+        });
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root)
+    }
+
+    /// x 以下の要素数
+    fn rank_upper(&self, x: &T) -> usize {
+        self.count_by(|v| v <= x)
+    }
+
+    /// `f(&val)` が true となる要素数（f は単調であること）
+    fn count_by<F: Fn(&T) -> bool>(&self, f: F) -> usize {
+        let mut t = &self.root;
+        let mut res = 0;
+        while let Some(n) = t {
+            if f(&n.val) {
+                res += size(&n.left) + 1;
+                t = &n.right;
+            } else {
+                t = &n.left;
+            }
+        }
+        res
+    }
+}
+
+impl<T: Ord> std::iter::FromIterator<T> for OrderedMultiset<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for x in iter {
+            set.insert(x);
+        }
+        set
+    }
+}
+
+/// `OrderedMultiset` の要素の位置を指すカーソル
+///
+/// 末尾の次（`index() == len()`）を指すこともある。`get` と移動はそれぞれ$O(log(N))$
+pub struct Cursor<'a, T: Ord> {
+    set: &'a OrderedMultiset<T>,
+    index: usize,
+}
+
+impl<'a, T: Ord> Cursor<'a, T> {
+    /// 指している要素の順位（小さい方から何番目か）
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 指している要素。末尾の次を指していれば `None`
+    pub fn get(&self) -> Option<&'a T> {
+        self.set.nth(self.index)
+    }
+
+    /// 1つ次の要素へ移動する。既に末尾の次なら false を返す
+    pub fn move_next(&mut self) -> bool {
+        if self.index < self.set.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 1つ前の要素へ移動する。既に先頭なら false を返す
+    pub fn move_prev(&mut self) -> bool {
+        if self.index > 0 {
+            self.index -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 1つ前の要素
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        self.index.checked_sub(1).and_then(|k| self.set.nth(k))
+    }
+}
+
+/// 位置で要素を管理する列（implicit treap）
+pub struct ImplicitTreap<T> {
+    root: Tree<T>,
+    rng: XorShift,
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            rng: XorShift::default(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// 位置 i に x を挿入する
+    pub fn insert(&mut self, i: usize, x: T) {
+        assert!(i <= self.len());
+        let (l, r) = split_at(self.root.take(), i);
+        let node = Some(Node::new(x, self.rng.next()));
+        self.root = merge(merge(l, node), r);
+    }
+
+    pub fn push_back(&mut self, x: T) {
+        let node = Some(Node::new(x, self.rng.next()));
+        self.root = merge(self.root.take(), node);
+    }
+
+    /// 位置 i の要素を削除して返す
+    pub fn remove(&mut self, i: usize) -> T {
+        assert!(i < self.len());
+        let (l, r) = split_at(self.root.take(), i);
+        let (m, r) = split_at(r, 1);
+        self.root = merge(l, r);
+        m.unwrap().val
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        nth(&self.root, i)
+    }
+
+    /// 区間 `range` を反転する
+    pub fn reverse<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (l, r) = range_to_pair(range, self.len());
+        assert!(l <= r && r <= self.len());
+        let (a, b) = split_at(self.root.take(), l);
+        let (mut b, c) = split_at(b, r - l);
+        if let Some(n) = b.as_mut() {
+            n.rev ^= true;
+        }
+        self.root = merge(merge(a, b), c);
+    }
+
+    /// 位置 at 以降の要素を切り離して返す
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len());
+        let (l, r) = split_at(self.root.take(), at);
+        self.root = l;
+        Self {
+            root: r,
+            rng: self.rng.fork(),
+        }
+    }
+
+    /// other の要素をすべて末尾に移す
+    pub fn append(&mut self, other: &mut Self) {
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::new(&self.root)
+    }
+}
+
+impl<T> std::iter::FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut seq = Self::new();
+        for x in iter {
+            seq.push_back(x);
+        }
+        seq
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn xorshift_fork_test() {
+        let mut a = XorShift::default();
+        let mut b = a.fork();
+        let xs: Vec<u64> = (0..100).map(|_| a.next()).collect();
+        let ys: Vec<u64> = (0..100).map(|_| b.next()).collect();
+        assert!(xs.iter().all(|x| !ys.contains(x)));
+    }
+
+    #[test]
+    fn ordered_multiset_test() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let mut set = OrderedMultiset::new();
+        let mut naive: Vec<i32> = Vec::new();
+        for _ in 0..2000 {
+            let x = rng.random_range(0..50);
+            match rng.random_range(0..4) {
+                0 | 1 => {
+                    set.insert(x);
+                    let pos = naive.partition_point(|&v| v < x);
+                    naive.insert(pos, x);
+                }
+                2 => {
+                    let pos = naive.iter().position(|&v| v == x);
+                    assert_eq!(set.remove(&x), pos.is_some());
+                    if let Some(pos) = pos {
+                        naive.remove(pos);
+                    }
+                }
+                _ => {
+                    let lower = naive.partition_point(|&v| v < x);
+                    let upper = naive.partition_point(|&v| v <= x);
+                    assert_eq!(set.rank(&x), lower);
+                    assert_eq!(set.count(&x), upper - lower);
+                    assert_eq!(set.lower_bound(&x).get(), naive.get(lower));
+                    assert_eq!(set.upper_bound(&x).index(), upper);
+                    assert_eq!(set.upper_bound(&x).get(), naive.get(upper));
+                }
+            }
+            assert_eq!(set.len(), naive.len());
+            let k = rng.random_range(0..60);
+            assert_eq!(set.nth(k), naive.get(k));
+        }
+        assert!(set.iter().eq(naive.iter()));
+    }
+
+    #[test]
+    fn ordered_multiset_split_merge_test() {
+        let mut set: OrderedMultiset<i32> = vec![5, 1, 3, 3, 9, 7].into_iter().collect();
+        assert!(!set.insert_unique(3));
+        assert!(set.insert_unique(4));
+        let mut upper = set.split_off(&4);
+        assert_eq!(set.iter().copied().collect::<Vec<_>>(), vec![1, 3, 3]);
+        assert_eq!(upper.iter().copied().collect::<Vec<_>>(), vec![4, 5, 7, 9]);
+        set.append(&mut upper);
+        assert!(upper.is_empty());
+        assert_eq!(set.len(), 7);
+        assert_eq!(set.first(), Some(&1));
+        assert_eq!(set.last(), Some(&9));
+
+        let mut cursor = set.lower_bound(&6);
+        assert_eq!(cursor.get(), Some(&7));
+        assert_eq!(cursor.peek_prev(), Some(&5));
+        assert!(cursor.move_next());
+        assert_eq!(cursor.get(), Some(&9));
+        assert!(cursor.move_next());
+        assert_eq!(cursor.get(), None);
+        assert!(!cursor.move_next());
+        assert!(cursor.move_prev());
+        assert_eq!(cursor.get(), Some(&9));
+    }
+
+    #[test]
+    fn implicit_treap_test() {
+        let mut rng = StdRng::seed_from_u64(18);
+        let mut seq = ImplicitTreap::new();
+        let mut naive: Vec<u32> = Vec::new();
+        for step in 0..2000 {
+            match rng.random_range(0..4) {
+                0 | 1 => {
+                    let i = rng.random_range(0..=naive.len());
+                    seq.insert(i, step);
+                    naive.insert(i, step);
+                }
+                2 if !naive.is_empty() => {
+                    let i = rng.random_range(0..naive.len());
+                    assert_eq!(seq.remove(i), naive.remove(i));
+                }
+                _ => {
+                    let l = rng.random_range(0..=naive.len());
+                    let r = rng.random_range(l..=naive.len());
+                    seq.reverse(l..r);
+                    naive[l..r].reverse();
+                }
+            }
+            assert_eq!(seq.len(), naive.len());
+            if !naive.is_empty() {
+                let i = rng.random_range(0..naive.len());
+                assert_eq!(seq.get(i), Some(&naive[i]));
+            }
+        }
+        assert!(seq.iter().eq(naive.iter()));
+
+        let mut tail = seq.split_off(naive.len() / 2);
+        let tail_naive = naive.split_off(naive.len() / 2);
+        assert!(tail.iter().eq(tail_naive.iter()));
+        tail.reverse(..);
+        seq.append(&mut tail);
+        naive.extend(tail_naive.into_iter().rev());
+        assert!(seq.iter().eq(naive.iter()));
+    }
+}
@@ -1,71 +1,475 @@
+use super::algebra::Group;
+use std::cell::{Cell, RefCell};
+
+/// Union-Find
+///
+/// 代表元の探索は経路半減で行う。内部可変性で経路を書き換えるので、`find` などは `&self` で呼べる
+#[derive(Clone, Debug)]
 pub struct UnionFind {
     n: usize,
-    parent_or_size: Vec<i32>,
+    parent: Vec<Cell<usize>>,
+    size: Vec<usize>,
+    group_count: usize,
+}
+
+/// 範囲外の要素を指定したときのエラー
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfRange {
+    pub index: usize,
+    pub len: usize,
+}
+
+impl std::fmt::Display for OutOfRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "index {} is out of range for {} elements",
+            self.index, self.len
+        )
+    }
 }
 
+impl std::error::Error for OutOfRange {}
+
 impl UnionFind {
     pub fn new(size: usize) -> Self {
         Self {
             n: size,
-            parent_or_size: vec![-1; size],
+            parent: (0..size).map(Cell::new).collect(),
+            size: vec![1; size],
+            group_count: size,
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// a と b を併合して代表元を返す
     pub fn merge(&mut self, a: usize, b: usize) -> usize {
-        let mut x = self.parent(a);
-        let mut y = self.parent(b);
+        let mut x = self.find(a);
+        let mut y = self.find(b);
         if x == y {
             return x;
         }
-        if -self.parent_or_size[x] < -self.parent_or_size[y] {
+        if self.size[x] < self.size[y] {
             std::mem::swap(&mut x, &mut y);
         }
-        self.parent_or_size[x] += self.parent_or_size[y];
-        self.parent_or_size[y] = x as i32;
+        self.size[x] += self.size[y];
+        self.parent[y].set(x);
+        self.group_count -= 1;
         x
     }
 
-    pub fn same(&mut self, a: usize, b: usize) -> bool {
-        self.parent(a) == self.parent(b)
+    /// 範囲外の要素を指定したときに panic せず `Err` を返す `merge`
+    pub fn try_merge(&mut self, a: usize, b: usize) -> Result<usize, OutOfRange> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(self.merge(a, b))
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// 範囲外の要素を指定したときに panic せず `Err` を返す `same`
+    pub fn try_same(&self, a: usize, b: usize) -> Result<bool, OutOfRange> {
+        self.check(a)?;
+        self.check(b)?;
+        Ok(self.same(a, b))
+    }
+
+    /// a の代表元
+    pub fn find(&self, a: usize) -> usize {
+        let mut a = a;
+        loop {
+            let p = self.parent[a].get();
+            if p == a {
+                return a;
+            }
+            let gp = self.parent[p].get();
+            self.parent[a].set(gp);
+            a = gp;
+        }
+    }
+
+    /// 範囲外の要素を指定したときに panic せず `Err` を返す `find`
+    pub fn try_find(&self, a: usize) -> Result<usize, OutOfRange> {
+        self.check(a)?;
+        Ok(self.find(a))
+    }
+
+    #[deprecated = "find を使う"]
+    pub fn parent(&self, a: usize) -> usize {
+        self.find(a)
+    }
+
+    pub fn size(&self, a: usize) -> usize {
+        self.size[self.find(a)]
+    }
+
+    /// 範囲外の要素を指定したときに panic せず `Err` を返す `size`
+    pub fn try_size(&self, a: usize) -> Result<usize, OutOfRange> {
+        self.check(a)?;
+        Ok(self.size(a))
+    }
+
+    /// グループの数
+    pub fn group_count(&self) -> usize {
+        self.group_count
+    }
+
+    /// 各要素の代表元
+    pub fn leader_of_each(&self) -> Vec<usize> {
+        (0..self.n).map(|i| self.find(i)).collect()
+    }
+
+    /// グループを代表元の昇順に1つずつ返す。各グループの要素は昇順
+    ///
+    /// 呼び出し時に代表元と同じグループの要素をつなぐ連結リストを$O(N)$で作り、
+    /// 各グループの `Vec` は取り出すときに作る
+    pub fn groups(&self) -> impl Iterator<Item = Vec<usize>> {
+        groups_from_leaders(self.leader_of_each())
+    }
+
+    pub fn make_groups(&self) -> Vec<Vec<usize>> {
+        self.groups().collect()
+    }
+
+    fn check(&self, a: usize) -> Result<(), OutOfRange> {
+        if a < self.n {
+            Ok(())
+        } else {
+            Err(OutOfRange {
+                index: a,
+                len: self.n,
+            })
+        }
+    }
+}
+
+/// 各要素の代表元から、グループを代表元の昇順に列挙する。各グループの要素は昇順
+fn groups_from_leaders(leaders: Vec<usize>) -> impl Iterator<Item = Vec<usize>> {
+    let n = leaders.len();
+    // 同じグループの要素を昇順につなぐ連結リスト
+    let mut size = vec![0; n];
+    let mut head = vec![usize::MAX; n];
+    let mut next = vec![usize::MAX; n];
+    for i in (0..n).rev() {
+        let p = leaders[i];
+        size[p] += 1;
+        next[i] = head[p];
+        head[p] = i;
     }
+    (0..n).filter(move |&r| leaders[r] == r).map(move |r| {
+        let mut group = Vec::with_capacity(size[r]);
+        let mut v = head[r];
+        while v != usize::MAX {
+            group.push(v);
+            v = next[v];
+        }
+        group
+    })
+}
+
+/// `WeightedUnionFind::merge` で既存の制約と矛盾する制約を追加しようとしたときのエラー
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Contradiction;
 
-    pub fn parent(&mut self, a: usize) -> usize {
-        if self.parent_or_size[a] < 0 {
+impl std::fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "the constraint contradicts the existing ones")
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+/// 重み付き Union-Find
+///
+/// 各要素にポテンシャル $x_i$（可換群 `G` の元）があり、$x_b - x_a = w$ という制約を追加しながら
+/// 同じグループに属する2要素のポテンシャルの差を求められる。
+/// `UnionFind` と同じく内部可変性で経路を書き換えるので、`find` などは `&self` で呼べる
+pub struct WeightedUnionFind<G: Group> {
+    n: usize,
+    parent: Vec<Cell<usize>>,
+    size: Vec<usize>,
+    // 親のポテンシャルに対する自身のポテンシャルの差
+    diff_weight: RefCell<Vec<G::S>>,
+}
+
+impl<G: Group> WeightedUnionFind<G> {
+    pub fn new(size: usize) -> Self {
+        Self {
+            n: size,
+            parent: (0..size).map(Cell::new).collect(),
+            size: vec![1; size],
+            diff_weight: RefCell::new(vec![G::identity(); size]),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $x_b - x_a = w$ という制約を追加する
+    ///
+    /// 既に a と b が同じグループで、制約が矛盾する場合は何もせず `Err(Contradiction)` を返す
+    pub fn merge(&mut self, a: usize, b: usize, w: G::S) -> Result<(), Contradiction>
+    where
+        G::S: PartialEq,
+    {
+        let mut x = self.find(a);
+        let mut y = self.find(b);
+        let diff_weight = self.diff_weight.get_mut();
+        // x_y - x_x
+        let mut w = G::op(&G::op(&w, &diff_weight[a]), &G::inverse(&diff_weight[b]));
+        if x == y {
+            return if w == G::identity() {
+                Ok(())
+            } else {
+                Err(Contradiction)
+            };
+        }
+        if self.size[x] < self.size[y] {
+            std::mem::swap(&mut x, &mut y);
+            w = G::inverse(&w);
+        }
+        self.size[x] += self.size[y];
+        self.parent[y].set(x);
+        diff_weight[y] = w;
+        Ok(())
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// a の代表元
+    ///
+    /// 経路圧縮をし、a のポテンシャルの差を代表元に対するものに書き換える
+    pub fn find(&self, a: usize) -> usize {
+        let p = self.parent[a].get();
+        if p == a {
             return a;
         }
-        self.parent_or_size[a] = self.parent(self.parent_or_size[a] as usize) as i32;
-        self.parent_or_size[a] as usize
+        // union by size なので再帰の深さは O(log(N))
+        let root = self.find(p);
+        let mut diff_weight = self.diff_weight.borrow_mut();
+        diff_weight[a] = G::op(&diff_weight[a], &diff_weight[p]);
+        self.parent[a].set(root);
+        root
     }
 
-    pub fn size(&mut self, a: usize) -> usize {
-        let x = self.parent(a);
-        -self.parent_or_size[x] as usize
+    pub fn size(&self, a: usize) -> usize {
+        self.size[self.find(a)]
     }
 
-    pub fn make_groups(&mut self) -> Vec<Vec<usize>> {
-        let mut parent_buf = vec![0; self.n];
-        let mut group_size = vec![0; self.n];
-        for i in 0..self.n {
-            parent_buf[i] = self.parent(i);
-            group_size[parent_buf[i]] += 1;
+    /// a と b が同じグループなら $x_b - x_a$ を返す
+    pub fn diff(&self, a: usize, b: usize) -> Option<G::S> {
+        if !self.same(a, b) {
+            return None;
         }
-        let mut res = vec![Vec::new(); self.n];
-        for i in 0..self.n {
-            res[i].reserve(group_size[i]);
+        let diff_weight = self.diff_weight.borrow();
+        Some(G::op(&diff_weight[b], &G::inverse(&diff_weight[a])))
+    }
+
+    pub fn make_groups(&self) -> Vec<Vec<usize>> {
+        let leaders = (0..self.n).map(|i| self.find(i)).collect::<Vec<_>>();
+        groups_from_leaders(leaders).collect()
+    }
+}
+
+/// 操作を巻き戻せる Union-Find
+///
+/// 経路圧縮をせずに union by size のみを行うので、各操作は$O(log(N))$
+pub struct RollbackUnionFind {
+    n: usize,
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    // merge ごとの (併合後の代表元, その下につないだ元の代表元)。既に同じグループなら両者は等しい
+    history: Vec<(usize, usize)>,
+}
+
+impl RollbackUnionFind {
+    pub fn new(size: usize) -> Self {
+        Self {
+            n: size,
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            history: Vec::new(),
         }
-        for i in 0..self.n {
-            res[parent_buf[i]].push(i);
+    }
+
+    /// a と b を併合して代表元を返す。既に同じグループでも履歴には積まれる
+    pub fn merge(&mut self, a: usize, b: usize) -> usize {
+        let mut x = self.parent(a);
+        let mut y = self.parent(b);
+        if self.size[x] < self.size[y] {
+            std::mem::swap(&mut x, &mut y);
         }
-        res
-            .into_iter()
-            .filter(|x| !x.is_empty())
-            .collect()
+        self.history.push((x, y));
+        if x == y {
+            return x;
+        }
+        self.size[x] += self.size[y];
+        self.parent[y] = x;
+        x
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.parent(a) == self.parent(b)
+    }
+
+    pub fn parent(&self, a: usize) -> usize {
+        let mut a = a;
+        while self.parent[a] != a {
+            a = self.parent[a];
+        }
+        a
+    }
+
+    pub fn size(&self, a: usize) -> usize {
+        self.size[self.parent(a)]
+    }
+
+    /// 現在の状態を表す値を返す。`rollback` に渡すとこの状態に戻せる
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// 直前の `merge` を取り消す。取り消す操作がなければ false を返す
+    pub fn undo(&mut self) -> bool {
+        match self.history.pop() {
+            Some((x, y)) => {
+                if x != y {
+                    self.parent[y] = y;
+                    self.size[x] -= self.size[y];
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `snapshot` で得た状態まで巻き戻す
+    pub fn rollback(&mut self, to: usize) {
+        assert!(to <= self.history.len());
+        while self.history.len() > to {
+            self.undo();
+        }
+    }
+
+    pub fn make_groups(&self) -> Vec<Vec<usize>> {
+        let leaders = (0..self.n).map(|i| self.parent(i)).collect::<Vec<_>>();
+        groups_from_leaders(leaders).collect()
+    }
+}
+
+/// 部分永続 Union-Find
+///
+/// t 回目の `merge` を時刻 t に行ったとみなし、過去の任意の時刻の状態に対する質問に$O(log(N))$で答える。
+/// 時刻 0 は一度も併合していない状態
+pub struct PartiallyPersistentUnionFind {
+    now: usize,
+    parent: Vec<usize>,
+    // 根でなくなった時刻（根なら usize::MAX）
+    time: Vec<usize>,
+    // 根であった間の (時刻, サイズ) の履歴
+    size_history: Vec<Vec<(usize, usize)>>,
+}
+
+impl PartiallyPersistentUnionFind {
+    pub fn new(size: usize) -> Self {
+        Self {
+            now: 0,
+            parent: (0..size).collect(),
+            time: vec![usize::MAX; size],
+            size_history: vec![vec![(0, 1)]; size],
+        }
+    }
+
+    /// 現在の時刻（これまでの `merge` の回数）
+    pub fn now(&self) -> usize {
+        self.now
+    }
+
+    /// a と b を併合し、その時刻を返す
+    pub fn merge(&mut self, a: usize, b: usize) -> usize {
+        self.now += 1;
+        let t = self.now;
+        let mut x = self.parent_at(a, t);
+        let mut y = self.parent_at(b, t);
+        if x == y {
+            return t;
+        }
+        let (sx, sy) = (self.current_size(x), self.current_size(y));
+        if sx < sy {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parent[y] = x;
+        self.time[y] = t;
+        self.size_history[x].push((t, sx + sy));
+        t
+    }
+
+    /// 時刻 t における a の代表元
+    pub fn parent_at(&self, a: usize, t: usize) -> usize {
+        let mut a = a;
+        while self.time[a] <= t {
+            a = self.parent[a];
+        }
+        a
+    }
+
+    /// 時刻 t において a と b が同じグループか
+    pub fn same_at(&self, a: usize, b: usize, t: usize) -> bool {
+        self.parent_at(a, t) == self.parent_at(b, t)
+    }
+
+    /// 時刻 t における a が属するグループの大きさ
+    pub fn size_at(&self, a: usize, t: usize) -> usize {
+        let x = self.parent_at(a, t);
+        let history = &self.size_history[x];
+        let i = history.partition_point(|&(s, _)| s <= t);
+        history[i - 1].1
+    }
+
+    /// a と b が初めて同じグループになった時刻。まだ同じグループでなければ `None`
+    pub fn connected_time(&self, a: usize, b: usize) -> Option<usize> {
+        let (mut a, mut b) = (a, b);
+        let mut res = 0;
+        while a != b {
+            if self.time[a] > self.time[b] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            if self.time[a] == usize::MAX {
+                return None;
+            }
+            res = self.time[a];
+            a = self.parent[a];
+        }
+        Some(res)
+    }
+
+    fn current_size(&self, x: usize) -> usize {
+        self.size_history[x].last().unwrap().1
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::data_struct::algebra::Additive;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
 
     #[test]
     fn uf_test() {
@@ -78,4 +482,133 @@ mod tests {
         assert!(!d.same(0, 3));
         assert_eq!(d.make_groups(), vec![vec![0, 1, 2], vec![3]]);
     }
+
+    #[test]
+    fn uf_checked_test() {
+        let mut d = UnionFind::new(6);
+        assert_eq!(d.group_count(), 6);
+        assert_eq!(d.try_merge(5, 2), Ok(5));
+        assert_eq!(d.try_merge(3, 2), Ok(5));
+        assert_eq!(d.try_merge(0, 6), Err(OutOfRange { index: 6, len: 6 }));
+        assert_eq!(d.try_same(7, 0), Err(OutOfRange { index: 7, len: 6 }));
+        assert_eq!(d.try_find(3), Ok(5));
+        d.merge(1, 0);
+        assert_eq!(d.group_count(), 3);
+        assert_eq!(d.leader_of_each(), vec![1, 1, 5, 5, 4, 5]);
+        let groups = d.groups().collect::<Vec<_>>();
+        assert_eq!(groups, vec![vec![0, 1], vec![4], vec![2, 3, 5]]);
+        assert_eq!(d.make_groups(), groups);
+        assert_eq!(d.groups().next(), Some(vec![0, 1]));
+        assert_eq!(d.size(3), 3);
+        assert_eq!(d.try_size(2), Ok(3));
+        assert_eq!(d.try_size(6), Err(OutOfRange { index: 6, len: 6 }));
+    }
+
+    #[test]
+    fn weighted_uf_test() {
+        let mut d = WeightedUnionFind::<Additive<i64>>::new(5);
+        assert_eq!(d.len(), 5);
+        assert_eq!(d.merge(0, 1, 3), Ok(()));
+        assert_eq!(d.merge(2, 1, -2), Ok(()));
+        assert_eq!(d.diff(0, 2), Some(5));
+        assert_eq!(d.diff(2, 0), Some(-5));
+        assert_eq!(d.merge(0, 2, 5), Ok(()));
+        assert_eq!(d.merge(0, 2, 4), Err(Contradiction));
+        assert_eq!(d.diff(0, 3), None);
+        assert_eq!(d.merge(4, 3, 10), Ok(()));
+        assert_eq!(d.merge(3, 0, 1), Ok(()));
+        assert_eq!(d.diff(4, 2), Some(16));
+        assert_eq!(d.size(4), 5);
+        assert_eq!(d.find(1), d.find(4));
+        assert_eq!(d.make_groups(), vec![vec![0, 1, 2, 3, 4]]);
+    }
+
+    #[test]
+    fn weighted_uf_random_test() {
+        let mut rng = StdRng::seed_from_u64(10);
+        let n = 30;
+        let x: Vec<i64> = (0..n).map(|_| rng.random_range(-1000..=1000)).collect();
+        let mut d = WeightedUnionFind::<Additive<i64>>::new(n);
+        let mut naive = UnionFind::new(n);
+        for _ in 0..100 {
+            let a = rng.random_range(0..n);
+            let b = rng.random_range(0..n);
+            if rng.random_bool(0.8) {
+                assert_eq!(d.merge(a, b, x[b] - x[a]), Ok(()));
+                naive.merge(a, b);
+            } else if d.same(a, b) {
+                assert_eq!(d.merge(a, b, x[b] - x[a] + 1), Err(Contradiction));
+            }
+            let (a, b) = (rng.random_range(0..n), rng.random_range(0..n));
+            let expected = if naive.same(a, b) {
+                Some(x[b] - x[a])
+            } else {
+                None
+            };
+            assert_eq!(d.diff(a, b), expected);
+        }
+        assert_eq!(d.make_groups(), naive.make_groups());
+    }
+
+    #[test]
+    fn rollback_uf_test() {
+        let mut d = RollbackUnionFind::new(5);
+        d.merge(0, 1);
+        let snap = d.snapshot();
+        d.merge(1, 2);
+        d.merge(3, 4);
+        d.merge(0, 2);
+        assert!(d.same(0, 2));
+        assert_eq!(d.size(2), 3);
+        assert!(d.undo());
+        assert!(d.same(0, 2));
+        assert!(d.undo());
+        assert!(!d.same(3, 4));
+        d.rollback(snap);
+        assert!(!d.same(0, 2));
+        assert!(d.same(0, 1));
+        assert_eq!(d.make_groups(), vec![vec![0, 1], vec![2], vec![3], vec![4]]);
+        assert_eq!(d.size(1), 2);
+        d.rollback(0);
+        assert!(!d.undo());
+        assert_eq!(d.size(0), 1);
+        assert_eq!(d.size(1), 1);
+    }
+
+    #[test]
+    fn partially_persistent_uf_test() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let n = 20;
+        let mut d = PartiallyPersistentUnionFind::new(n);
+        let mut snapshots = vec![UnionFind::new(n)];
+        for _ in 0..40 {
+            let (a, b) = (rng.random_range(0..n), rng.random_range(0..n));
+            let t = d.merge(a, b);
+            let mut uf = UnionFind::new(n);
+            for i in 0..n {
+                for j in 0..n {
+                    if snapshots[t - 1].same(i, j) {
+                        uf.merge(i, j);
+                    }
+                }
+            }
+            uf.merge(a, b);
+            snapshots.push(uf);
+        }
+        assert_eq!(d.now(), 40);
+        for (t, uf) in snapshots.iter().enumerate() {
+            for a in 0..n {
+                assert_eq!(d.size_at(a, t), uf.size(a));
+                for b in 0..n {
+                    assert_eq!(d.same_at(a, b, t), uf.same(a, b));
+                }
+            }
+        }
+        for a in 0..n {
+            for b in 0..n {
+                let expected = (0..=d.now()).find(|&t| snapshots[t].same(a, b));
+                assert_eq!(d.connected_time(a, b), expected);
+            }
+        }
+    }
 }
@@ -0,0 +1,406 @@
+//! Wavelet Matrix
+//!
+//! 非負整数の静的な列 $a_0, a_1, \cdots , a_{n-1}$ に対して以下の様なことができる
+//! - $a_i$ の取得
+//! - 区間内の値 x の個数（`rank`）
+//! - 区間内で k 番目に小さい値（`kth_smallest`）
+//! - 区間内で値が $[lo, hi)$ に含まれるものの個数（`range_freq`）
+//! - 区間内で x 未満の最大値・x 以上の最小値（`prev_value`, `next_value`）
+//! - 計算量はいずれも$O(log(max a_i))$
+//!
+//! `WaveletMatrixWithBIT` は各要素に重みを持たせ、一点の重みの加算と
+//! 「区間内で値が $[lo, hi)$ に含まれる要素の重みの総和」を$O(log(N) log(max a_i))$で行う
+
+use super::algebra::Group;
+use super::bit::{range_to_pair, BIT};
+use std::ops::{Bound, RangeBounds};
+
+/// 完備辞書（rank のみ）
+#[derive(Clone, Debug)]
+struct BitVector {
+    bits: Vec<u64>,
+    // cum[i] は先頭 64i ビット中の1の個数
+    cum: Vec<usize>,
+}
+
+impl BitVector {
+    fn new(v: &[bool]) -> Self {
+        let mut bits = vec![0u64; v.len() / 64 + 1];
+        for (i, &b) in v.iter().enumerate() {
+            if b {
+                bits[i / 64] |= 1 << (i % 64);
+            }
+        }
+        let mut cum = vec![0; bits.len() + 1];
+        for i in 0..bits.len() {
+            cum[i + 1] = cum[i] + bits[i].count_ones() as usize;
+        }
+        Self { bits, cum }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.bits[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    /// [0, i) 中の1の個数
+    fn rank1(&self, i: usize) -> usize {
+        let mask = (1u64 << (i % 64)).wrapping_sub(1);
+        self.cum[i / 64] + (self.bits[i / 64] & mask).count_ones() as usize
+    }
+
+    /// [0, i) 中の0の個数
+    fn rank0(&self, i: usize) -> usize {
+        i - self.rank1(i)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WaveletMatrix {
+    n: usize,
+    log: usize,
+    // levels[d] は上から d 番目のビット
+    levels: Vec<BitVector>,
+    zeros: Vec<usize>,
+}
+
+impl WaveletMatrix {
+    pub fn new(a: &[u64]) -> Self {
+        let n = a.len();
+        let max = a.iter().copied().max().unwrap_or(0);
+        let log = ((u64::BITS - max.leading_zeros()) as usize).max(1);
+        let mut levels = Vec::with_capacity(log);
+        let mut zeros = Vec::with_capacity(log);
+        let mut cur = a.to_vec();
+        for d in 0..log {
+            let shift = log - 1 - d;
+            let bits: Vec<bool> = cur.iter().map(|&x| x >> shift & 1 == 1).collect();
+            levels.push(BitVector::new(&bits));
+            let (mut left, right): (Vec<u64>, Vec<u64>) =
+                cur.iter().partition(|&&x| x >> shift & 1 == 0);
+            zeros.push(left.len());
+            left.extend(right);
+            cur = left;
+        }
+        Self {
+            n,
+            log,
+            levels,
+            zeros,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// $a_i$ を求める
+    pub fn access(&self, i: usize) -> u64 {
+        assert!(i < self.n);
+        let mut i = i;
+        let mut val = 0;
+        for d in 0..self.log {
+            if self.levels[d].get(i) {
+                val |= 1 << (self.log - 1 - d);
+                i = self.zeros[d] + self.levels[d].rank1(i);
+            } else {
+                i = self.levels[d].rank0(i);
+            }
+        }
+        val
+    }
+
+    /// 区間 `range` に含まれる x の個数
+    pub fn rank<R: RangeBounds<usize>>(&self, x: u64, range: R) -> usize {
+        let (mut l, mut r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        if self.exceeds(x) {
+            return 0;
+        }
+        for d in 0..self.log {
+            let (nl, nr) = self.next(d, l, r, x >> (self.log - 1 - d) & 1 == 1);
+            l = nl;
+            r = nr;
+        }
+        r - l
+    }
+
+    /// 区間 `range` で k 番目（0-indexed）に小さい値
+    pub fn kth_smallest<R: RangeBounds<usize>>(&self, range: R, k: usize) -> u64 {
+        let (mut l, mut r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        assert!(k < r - l);
+        let mut k = k;
+        let mut val = 0;
+        for d in 0..self.log {
+            let z = self.levels[d].rank0(r) - self.levels[d].rank0(l);
+            let bit = k >= z;
+            if bit {
+                k -= z;
+                val |= 1 << (self.log - 1 - d);
+            }
+            let (nl, nr) = self.next(d, l, r, bit);
+            l = nl;
+            r = nr;
+        }
+        val
+    }
+
+    /// 区間 `range` で k 番目（0-indexed）に大きい値
+    pub fn kth_largest<R: RangeBounds<usize>>(&self, range: R, k: usize) -> u64 {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r - l > k);
+        self.kth_smallest(l..r, r - l - 1 - k)
+    }
+
+    /// 区間 `range` に含まれる値のうち、`values` に含まれるものの個数
+    pub fn range_freq<R, V>(&self, range: R, values: V) -> usize
+    where
+        R: RangeBounds<usize>,
+        V: RangeBounds<u64>,
+    {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        let lo = match values.start_bound() {
+            Bound::Included(&x) => self.count_less(l, r, x),
+            Bound::Excluded(&x) => self.count_less_eq(l, r, x),
+            Bound::Unbounded => 0,
+        };
+        let hi = match values.end_bound() {
+            Bound::Included(&x) => self.count_less_eq(l, r, x),
+            Bound::Excluded(&x) => self.count_less(l, r, x),
+            Bound::Unbounded => r - l,
+        };
+        hi.saturating_sub(lo)
+    }
+
+    /// 区間 `range` に含まれる x 未満の最大値
+    pub fn prev_value<R: RangeBounds<usize>>(&self, range: R, x: u64) -> Option<u64> {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        let cnt = self.count_less(l, r, x);
+        if cnt == 0 {
+            None
+        } else {
+            Some(self.kth_smallest(l..r, cnt - 1))
+        }
+    }
+
+    /// 区間 `range` に含まれる x 以上の最小値
+    pub fn next_value<R: RangeBounds<usize>>(&self, range: R, x: u64) -> Option<u64> {
+        let (l, r) = range_to_pair(range, self.n);
+        assert!(l <= r && r <= self.n);
+        let cnt = self.count_less(l, r, x);
+        if cnt == r - l {
+            None
+        } else {
+            Some(self.kth_smallest(l..r, cnt))
+        }
+    }
+
+    /// [l, r) に含まれる x 未満の値の個数
+    fn count_less(&self, l: usize, r: usize, x: u64) -> usize {
+        if self.exceeds(x) {
+            return r - l;
+        }
+        let (mut l, mut r) = (l, r);
+        let mut res = 0;
+        for d in 0..self.log {
+            let bit = x >> (self.log - 1 - d) & 1 == 1;
+            if bit {
+                res += self.levels[d].rank0(r) - self.levels[d].rank0(l);
+            }
+            let (nl, nr) = self.next(d, l, r, bit);
+            l = nl;
+            r = nr;
+        }
+        res
+    }
+
+    fn count_less_eq(&self, l: usize, r: usize, x: u64) -> usize {
+        match x.checked_add(1) {
+            Some(x) => self.count_less(l, r, x),
+            None => r - l,
+        }
+    }
+
+    /// x が log ビットに収まらないか
+    fn exceeds(&self, x: u64) -> bool {
+        self.log < 64 && x >> self.log != 0
+    }
+
+    /// 段 d で [l, r) のうちビットが `bit` の要素が、段 d + 1 で占める区間
+    fn next(&self, d: usize, l: usize, r: usize, bit: bool) -> (usize, usize) {
+        let level = &self.levels[d];
+        if bit {
+            (
+                self.zeros[d] + level.rank1(l),
+                self.zeros[d] + level.rank1(r),
+            )
+        } else {
+            (level.rank0(l), level.rank0(r))
+        }
+    }
+}
+
+/// 各段に BIT を持たせた Wavelet Matrix
+pub struct WaveletMatrixWithBIT<G: Group> {
+    wm: WaveletMatrix,
+    // bits[d] は段 d での並び順で重みを持つ（bits[log] は最後の並び替えの後）
+    bits: Vec<BIT<G>>,
+}
+
+impl<G: Group> WaveletMatrixWithBIT<G> {
+    /// 値の列 `a` に対して、重みがすべて単位元の状態で構築する
+    pub fn new(a: &[u64]) -> Self {
+        let wm = WaveletMatrix::new(a);
+        let bits = (0..=wm.log).map(|_| BIT::new(a.len())).collect();
+        Self { wm, bits }
+    }
+
+    pub fn matrix(&self) -> &WaveletMatrix {
+        &self.wm
+    }
+
+    /// i 番目の要素の重みに w を加算する
+    pub fn add(&mut self, i: usize, w: G::S) {
+        assert!(i < self.wm.n);
+        let mut i = i;
+        for d in 0..self.wm.log {
+            self.bits[d].add(i + 1, w.clone());
+            let bit = self.wm.levels[d].get(i);
+            i = self.wm.next(d, i, i + 1, bit).0;
+        }
+        self.bits[self.wm.log].add(i + 1, w);
+    }
+
+    /// 区間 `range` の要素のうち、値が `values` に含まれるものの重みの総和
+    pub fn sum<R, V>(&self, range: R, values: V) -> G::S
+    where
+        R: RangeBounds<usize>,
+        V: RangeBounds<u64>,
+    {
+        let (l, r) = range_to_pair(range, self.wm.n);
+        assert!(l <= r && r <= self.wm.n);
+        let lo = match values.start_bound() {
+            Bound::Included(&x) => self.sum_less(l, r, Some(x)),
+            Bound::Excluded(&x) => self.sum_less(l, r, x.checked_add(1)),
+            Bound::Unbounded => G::identity(),
+        };
+        let hi = match values.end_bound() {
+            Bound::Included(&x) => self.sum_less(l, r, x.checked_add(1)),
+            Bound::Excluded(&x) => self.sum_less(l, r, Some(x)),
+            Bound::Unbounded => self.bits[0].sum_range(l + 1..=r),
+        };
+        G::op(&hi, &G::inverse(&lo))
+    }
+
+    /// [l, r) のうち x 未満の値を持つ要素の重みの総和（x が `None` なら上限なし）
+    fn sum_less(&self, l: usize, r: usize, x: Option<u64>) -> G::S {
+        let log = self.wm.log;
+        let x = match x {
+            Some(x) if !self.wm.exceeds(x) => x,
+            _ => return self.bits[0].sum_range(l + 1..=r),
+        };
+        let (mut l, mut r) = (l, r);
+        let mut res = G::identity();
+        for d in 0..log {
+            let bit = x >> (log - 1 - d) & 1 == 1;
+            if bit {
+                let (zl, zr) = self.wm.next(d, l, r, false);
+                res = G::op(&res, &self.bits[d + 1].sum_range(zl + 1..=zr));
+            }
+            let (nl, nr) = self.wm.next(d, l, r, bit);
+            l = nl;
+            r = nr;
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_struct::algebra::Additive;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn wavelet_matrix_test() {
+        let mut rng = StdRng::seed_from_u64(15);
+        let n = 40;
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..20)).collect();
+        let wm = WaveletMatrix::new(&a);
+        for (i, &x) in a.iter().enumerate() {
+            assert_eq!(wm.access(i), x);
+        }
+        for _ in 0..500 {
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let mut sorted = a[l..r].to_vec();
+            sorted.sort();
+            let x = rng.random_range(0..25);
+            let y = rng.random_range(x..25);
+            assert_eq!(wm.rank(x, l..r), sorted.iter().filter(|&&v| v == x).count());
+            assert_eq!(
+                wm.range_freq(l..r, x..y),
+                sorted.iter().filter(|&&v| x <= v && v < y).count()
+            );
+            assert_eq!(
+                wm.range_freq(l..r, x..=y),
+                sorted.iter().filter(|&&v| x <= v && v <= y).count()
+            );
+            assert_eq!(
+                wm.prev_value(l..r, x),
+                sorted.iter().rev().find(|&&v| v < x).copied()
+            );
+            assert_eq!(
+                wm.next_value(l..r, x),
+                sorted.iter().find(|&&v| v >= x).copied()
+            );
+            if l < r {
+                let k = rng.random_range(0..r - l);
+                assert_eq!(wm.kth_smallest(l..r, k), sorted[k]);
+                assert_eq!(wm.kth_largest(l..r, k), sorted[r - l - 1 - k]);
+            }
+        }
+        let wm = WaveletMatrix::new(&[u64::MAX, 0, 1 << 63]);
+        assert_eq!(wm.access(0), u64::MAX);
+        assert_eq!(wm.range_freq(.., 1..), 2);
+        assert_eq!(wm.range_freq(.., (1 << 63)..=u64::MAX), 2);
+        assert_eq!(wm.kth_smallest(.., 1), 1 << 63);
+    }
+
+    #[test]
+    fn wavelet_matrix_with_bit_test() {
+        let mut rng = StdRng::seed_from_u64(16);
+        let n = 40;
+        let a: Vec<u64> = (0..n).map(|_| rng.random_range(0..20)).collect();
+        let mut wm = WaveletMatrixWithBIT::<Additive<i64>>::new(&a);
+        let mut w = vec![0i64; n];
+        for _ in 0..500 {
+            let i = rng.random_range(0..n);
+            let x = rng.random_range(-10..=10);
+            wm.add(i, x);
+            w[i] += x;
+            let l = rng.random_range(0..=n);
+            let r = rng.random_range(l..=n);
+            let lo = rng.random_range(0..25);
+            let hi = rng.random_range(lo..25);
+            let expected: i64 = (l..r)
+                .filter(|&j| lo <= a[j] && a[j] < hi)
+                .map(|j| w[j])
+                .sum();
+            assert_eq!(wm.sum(l..r, lo..hi), expected);
+            let expected: i64 = (l..r).filter(|&j| lo < a[j]).map(|j| w[j]).sum();
+            assert_eq!(
+                wm.sum(l..r, (Bound::Excluded(lo), Bound::Unbounded)),
+                expected
+            );
+        }
+        assert_eq!(wm.matrix().len(), n);
+    }
+}
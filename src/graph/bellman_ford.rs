@@ -0,0 +1,203 @@
+//! Bellman-Ford 法
+//!
+//! 負のコストの辺を含むグラフで、始点からの最短距離を求める
+//! - `bellman_ford`: $O(NM)$
+//! - `spfa`: キューで更新のあった頂点だけを見る。最悪$O(NM)$だが多くの場合速い
+//!
+//! どちらも、始点から到達できる負閉路から辿れる頂点の距離は `Distance::NegativeInfinity` になる
+
+use super::dijkstra::{Cost, Edge, Graph};
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distance<C> {
+    Unreachable,
+    Finite(C),
+    NegativeInfinity,
+}
+
+impl<C> Distance<C> {
+    /// 有限の距離なら `Some`
+    pub fn finite(self) -> Option<C> {
+        match self {
+            Distance::Finite(d) => Some(d),
+            _ => None,
+        }
+    }
+}
+
+pub fn bellman_ford<C: Cost>(graph: &Graph<C>, start: usize) -> Vec<Distance<C>> {
+    let len = graph.len();
+    let mut dist: Vec<Option<C>> = vec![None; len];
+
+    dist[start] = Some(C::ZERO);
+    for _ in 1..len {
+        let mut updated = false;
+        for v in 0..len {
+            if let Some(d) = dist[v] {
+                for &Edge { to, cost } in graph[v].iter() {
+                    let nd = d.plus(cost);
+                    if dist[to].map_or(true, |x| nd < x) {
+                        dist[to] = Some(nd);
+                        updated = true;
+                    }
+                }
+            }
+        }
+        if !updated {
+            break;
+        }
+    }
+
+    // N - 1 回の後でもまだ更新できる頂点は負閉路から辿れる
+    let negative: Vec<usize> = (0..len)
+        .filter(|&v| {
+            dist[v].is_some_and(|d| {
+                graph[v]
+                    .iter()
+                    .any(|e| dist[e.to].map_or(true, |x| d.plus(e.cost) < x))
+            })
+        })
+        .collect();
+    finish(graph, dist, negative)
+}
+
+pub fn spfa<C: Cost>(graph: &Graph<C>, start: usize) -> Vec<Distance<C>> {
+    let len = graph.len();
+    let mut dist: Vec<Option<C>> = vec![None; len];
+    // 現在の距離を与える経路の辺の本数。N 以上なら経路に負閉路を含む
+    let mut edges = vec![0; len];
+    let mut in_queue = vec![false; len];
+    let mut negative = Vec::new();
+    let mut queue = VecDeque::new();
+
+    dist[start] = Some(C::ZERO);
+    queue.push_back(start);
+    in_queue[start] = true;
+    while let Some(v) = queue.pop_front() {
+        in_queue[v] = false;
+        let d = dist[v].unwrap();
+        for &Edge { to, cost } in graph[v].iter() {
+            let nd = d.plus(cost);
+            if dist[to].map_or(true, |x| nd < x) {
+                dist[to] = Some(nd);
+                edges[to] = edges[v] + 1;
+                if edges[to] >= len {
+                    // これ以上は伸ばさず、後でまとめて負の無限大にする
+                    negative.push(to);
+                } else if !in_queue[to] {
+                    queue.push_back(to);
+                    in_queue[to] = true;
+                }
+            }
+        }
+    }
+
+    finish(graph, dist, negative)
+}
+
+/// `negative` から辿れる頂点を負の無限大にする
+fn finish<C: Cost>(
+    graph: &Graph<C>,
+    dist: Vec<Option<C>>,
+    mut negative: Vec<usize>,
+) -> Vec<Distance<C>> {
+    let mut res: Vec<Distance<C>> = dist
+        .into_iter()
+        .map(|d| d.map_or(Distance::Unreachable, Distance::Finite))
+        .collect();
+    for &v in negative.iter() {
+        res[v] = Distance::NegativeInfinity;
+    }
+    while let Some(v) = negative.pop() {
+        for &Edge { to, .. } in graph[v].iter() {
+            if res[to] != Distance::NegativeInfinity {
+                res[to] = Distance::NegativeInfinity;
+                negative.push(to);
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    /// 辺の本数が k 以下の歩道での最短距離を、k を十分大きくして比べる
+    fn naive(graph: &Graph<i64>, start: usize, max_abs: i64) -> Vec<Distance<i64>> {
+        let n = graph.len();
+        let walks = |k: usize| {
+            let mut dist = vec![None; n];
+            dist[start] = Some(0);
+            for _ in 0..k {
+                let mut next = dist.clone();
+                for (v, edges) in graph.iter().enumerate() {
+                    if let Some(d) = dist[v] {
+                        for e in edges {
+                            if next[e.to].map_or(true, |x| d + e.cost < x) {
+                                next[e.to] = Some(d + e.cost);
+                            }
+                        }
+                    }
+                }
+                dist = next;
+            }
+            dist
+        };
+        // 負閉路を十分な回数まわれば、N - 1 本以下の経路より必ず短くなる
+        let short = walks(n - 1);
+        let long = walks(2 * n + n * (3 * n * max_abs as usize + 1));
+        short
+            .iter()
+            .zip(long.iter())
+            .map(|(s, l)| match (s, l) {
+                (None, _) => Distance::Unreachable,
+                (Some(s), Some(l)) if l < s => Distance::NegativeInfinity,
+                (Some(s), _) => Distance::Finite(*s),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bellman_ford_test() {
+        let mut rng = StdRng::seed_from_u64(45);
+        for _ in 0..200 {
+            let n = rng.random_range(1..8);
+            let mut graph = vec![vec![]; n];
+            for _ in 0..rng.random_range(0..15) {
+                let u = rng.random_range(0..n);
+                let v = rng.random_range(0..n);
+                graph[u].push(Edge::new(v, rng.random_range(-3..=10)));
+            }
+            let start = rng.random_range(0..n);
+            let expected = naive(&graph, start, 10);
+            assert_eq!(bellman_ford(&graph, start), expected);
+            assert_eq!(spfa(&graph, start), expected);
+        }
+    }
+
+    #[test]
+    fn negative_cycle_test() {
+        // 0 -> 1 -> 2 -> 1 (負閉路) -> 3, 0 -> 4
+        let mut graph = vec![vec![]; 6];
+        graph[0].push(Edge::new(1, 1.5));
+        graph[1].push(Edge::new(2, -2.0));
+        graph[2].push(Edge::new(1, 1.0));
+        graph[2].push(Edge::new(3, 0.0));
+        graph[0].push(Edge::new(4, -0.5));
+        let expected = vec![
+            Distance::Finite(0.0),
+            Distance::NegativeInfinity,
+            Distance::NegativeInfinity,
+            Distance::NegativeInfinity,
+            Distance::Finite(-0.5),
+            Distance::Unreachable,
+        ];
+        assert_eq!(bellman_ford(&graph, 0), expected);
+        assert_eq!(spfa(&graph, 0), expected);
+        assert_eq!(expected[4].finite(), Some(-0.5));
+    }
+}
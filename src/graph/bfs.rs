@@ -0,0 +1,86 @@
+//! 幅優先探索
+//!
+//! `dijkstra` と同じ `Edge` のグラフで、最短距離を$O(N + M)$で求める
+//! - `bfs`: 辺のコストを無視し、辺の本数での距離を求める
+//! - `zero_one_bfs`: 辺のコストが 0 か 1 のときの距離を求める
+//!
+//! 到達できない頂点の距離は `None` になる
+
+use super::dijkstra::{Edge, Graph};
+use std::collections::VecDeque;
+
+pub fn bfs<C>(graph: &Graph<C>, start: usize) -> Vec<Option<usize>> {
+    let mut dist = vec![None; graph.len()];
+    let mut queue = VecDeque::new();
+
+    dist[start] = Some(0);
+    queue.push_back((0, start));
+    while let Some((d, v)) = queue.pop_front() {
+        for &Edge { to, .. } in graph[v].iter() {
+            if dist[to].is_none() {
+                dist[to] = Some(d + 1);
+                queue.push_back((d + 1, to));
+            }
+        }
+    }
+
+    dist
+}
+
+pub fn zero_one_bfs(graph: &Graph, start: usize) -> Vec<Option<usize>> {
+    let len = graph.len();
+    let mut dist: Vec<Option<usize>> = vec![None; len];
+    let mut done = vec![false; len];
+    let mut deque = VecDeque::new();
+
+    dist[start] = Some(0);
+    deque.push_back((0, start));
+    while let Some((d, v)) = deque.pop_front() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        for &Edge { to, cost } in graph[v].iter() {
+            assert!(cost <= 1, "cost must be 0 or 1");
+            let nd = d + cost;
+            if dist[to].map_or(true, |x| nd < x) {
+                dist[to] = Some(nd);
+                if cost == 0 {
+                    deque.push_front((nd, to));
+                } else {
+                    deque.push_back((nd, to));
+                }
+            }
+        }
+    }
+
+    dist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dijkstra::dijkstra;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn bfs_test() {
+        let mut rng = StdRng::seed_from_u64(44);
+        for _ in 0..100 {
+            let n = rng.random_range(1..15);
+            let mut graph = vec![vec![]; n];
+            let mut unit = vec![vec![]; n];
+            for _ in 0..rng.random_range(0..40) {
+                let u = rng.random_range(0..n);
+                let v = rng.random_range(0..n);
+                let c: usize = rng.random_range(0..=1);
+                graph[u].push(Edge::new(v, c));
+                unit[u].push(Edge::new(v, 1));
+            }
+            let start = rng.random_range(0..n);
+            assert_eq!(bfs(&graph, start), dijkstra(&unit, start));
+            assert_eq!(zero_one_bfs(&graph, start), dijkstra(&graph, start));
+        }
+    }
+}
@@ -1,46 +1,218 @@
+//! Dijkstra 法
+//!
+//! 辺のコストが非負のグラフで、始点からの最短距離を$O((N + M) log(M))$で求める
+//! - `dijkstra`: 1つの始点からの距離だけを求める
+//! - `dijkstra_with_path`: 複数の始点・終点での打ち切りに対応し、経路も復元できる
+//! - `count_shortest_paths`: 最短経路の本数も数える（`ModInt` などで数える）
+//!
+//! 辺のコストは `Cost` を実装した型で、到達できない頂点の距離は `None` になる
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::ops::Add;
+
+/// 辺のコスト。`PartialOrd` は全順序であること（f64 なら NaN を含まない）
+///
+/// ペア `(A, B)` は辞書順で比べ、成分ごとに足す（最短のうち辺数最小、などに使う）
+pub trait Cost: Copy + PartialOrd {
+    const ZERO: Self;
+    fn plus(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_cost {
+    ($($t:ty, $zero:expr);*) => {
+        $(
+            impl Cost for $t {
+                const ZERO: Self = $zero;
+                fn plus(self, rhs: Self) -> Self {
+                    self + rhs
+                }
+            }
+        )*
+    };
+}
+
+impl_cost!(u32, 0; u64, 0; usize, 0; i32, 0; i64, 0; f64, 0.0);
+
+impl<A: Cost, B: Cost> Cost for (A, B) {
+    const ZERO: Self = (A::ZERO, B::ZERO);
+    fn plus(self, rhs: Self) -> Self {
+        (self.0.plus(rhs.0), self.1.plus(rhs.1))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
-pub struct Edge {
-    to: usize,
-    cost: usize,
+pub struct Edge<C = usize> {
+    pub to: usize,
+    pub cost: C,
 }
 
-impl Edge {
-    pub fn new(to: usize, cost: usize) -> Edge {
+impl<C> Edge<C> {
+    pub fn new(to: usize, cost: C) -> Edge<C> {
         Edge { to, cost }
     }
 }
 
-pub type Graph = [Vec<Edge>];
-pub fn dijkstra(graph: &Graph, start: usize) -> Vec<usize> {
-    use std::cmp::Reverse;
-    use std::collections::BinaryHeap;
+pub type Graph<C = usize> = [Vec<Edge<C>>];
+
+/// ヒープに積む (距離, 頂点)。距離の小さい方が先に出る
+struct State<C>(C, usize);
+
+impl<C: PartialOrd> PartialEq for State<C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: PartialOrd> Eq for State<C> {}
 
-    let inf = 1 << 60;
+impl<C: PartialOrd> PartialOrd for State<C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: PartialOrd> Ord for State<C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .partial_cmp(&self.0)
+            .expect("cost must be totally ordered")
+    }
+}
+
+/// `dijkstra_with_path` の結果
+#[derive(Clone, Debug)]
+pub struct ShortestPath<C> {
+    dist: Vec<Option<C>>,
+    prev: Vec<Option<usize>>,
+}
+
+impl<C: Cost> ShortestPath<C> {
+    /// v までの最短距離
+    pub fn dist(&self, v: usize) -> Option<C> {
+        self.dist[v]
+    }
+
+    /// 各頂点までの最短距離
+    pub fn dists(&self) -> &[Option<C>] {
+        &self.dist
+    }
+
+    /// 最短経路木での v の親。始点や到達できない頂点では `None`
+    pub fn prev(&self, v: usize) -> Option<usize> {
+        self.prev[v]
+    }
+
+    /// いずれかの始点から v への最短経路の頂点列（両端を含む）
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        self.dist[v]?;
+        let mut path = vec![v];
+        while let Some(p) = self.prev[*path.last().unwrap()] {
+            path.push(p);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+pub fn dijkstra<C: Cost>(graph: &Graph<C>, start: usize) -> Vec<Option<C>> {
+    dijkstra_with_path(graph, &[start], None).dist
+}
+
+/// `sources` のいずれかからの最短距離と最短経路木
+///
+/// `target` を指定すると、その距離が確定した時点で打ち切る。
+/// このとき距離が確定していない頂点は `None` になる
+pub fn dijkstra_with_path<C: Cost>(
+    graph: &Graph<C>,
+    sources: &[usize],
+    target: Option<usize>,
+) -> ShortestPath<C> {
     let len = graph.len();
-    let mut dist = vec![inf; len];
+    let mut dist: Vec<Option<C>> = vec![None; len];
+    let mut prev = vec![None; len];
+    let mut done = vec![false; len];
     let mut heap = BinaryHeap::new();
 
-    dist[start] = 0;
-    heap.push(Reverse((dist[start], start)));
-    while let Some(Reverse((d, v))) = heap.pop() {
-        if dist[v] < d {
+    for &s in sources {
+        dist[s] = Some(C::ZERO);
+        heap.push(State(C::ZERO, s));
+    }
+    while let Some(State(d, v)) = heap.pop() {
+        if done[v] {
             continue;
         }
+        done[v] = true;
+        if target == Some(v) {
+            break;
+        }
         for &Edge { to, cost } in graph[v].iter() {
-            if dist[to] > dist[v] + cost {
-                dist[to] = dist[v] + cost;
-                heap.push(Reverse((dist[to], to)));
+            let nd = d.plus(cost);
+            if dist[to].map_or(true, |x| nd < x) {
+                dist[to] = Some(nd);
+                prev[to] = Some(v);
+                heap.push(State(nd, to));
             }
         }
     }
 
-    dist
+    for v in 0..len {
+        if !done[v] {
+            dist[v] = None;
+            prev[v] = None;
+        }
+    }
+    ShortestPath { dist, prev }
 }
 
+/// 始点からの最短距離と、最短経路の本数（辺のコストは正であること）
+pub fn count_shortest_paths<C, T>(graph: &Graph<C>, start: usize) -> Vec<Option<(C, T)>>
+where
+    C: Cost,
+    T: Copy + Add<Output = T> + num::Zero + num::One,
+{
+    let len = graph.len();
+    let mut dist: Vec<Option<C>> = vec![None; len];
+    let mut count = vec![T::zero(); len];
+    let mut done = vec![false; len];
+    let mut heap = BinaryHeap::new();
+
+    dist[start] = Some(C::ZERO);
+    count[start] = T::one();
+    heap.push(State(C::ZERO, start));
+    while let Some(State(d, v)) = heap.pop() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        for &Edge { to, cost } in graph[v].iter() {
+            let nd = d.plus(cost);
+            match dist[to] {
+                Some(x) if x < nd => {}
+                Some(x) if x == nd => count[to] = count[to] + count[v],
+                _ => {
+                    dist[to] = Some(nd);
+                    count[to] = count[v];
+                    heap.push(State(nd, to));
+                }
+            }
+        }
+    }
+
+    dist.into_iter()
+        .zip(count)
+        .map(|(d, c)| d.map(|d| (d, c)))
+        .collect()
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::math::modint::ModInt998244353;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
     #[test]
     fn dijkstra_test() {
         let start = 0;
@@ -53,6 +225,136 @@ mod tests {
         graph[2].push(Edge::new(4, 1));
 
         let dist = dijkstra(&graph, start);
-        assert_eq!(dist[4], 5);
+        assert_eq!(dist[4], Some(5));
+    }
+
+    fn random_graph(rng: &mut StdRng, n: usize, m: usize, max_cost: u64) -> Vec<Vec<Edge<u64>>> {
+        let mut graph = vec![vec![]; n];
+        for _ in 0..m {
+            let u = rng.random_range(0..n);
+            let v = rng.random_range(0..n);
+            let c = rng.random_range(1..=max_cost);
+            graph[u].push(Edge::new(v, c));
+        }
+        graph
+    }
+
+    /// 全点対の最短距離
+    fn naive_dist(graph: &Graph<u64>) -> Vec<Vec<Option<u64>>> {
+        let n = graph.len();
+        let mut d = vec![vec![None; n]; n];
+        for (u, edges) in graph.iter().enumerate() {
+            d[u][u] = Some(0);
+            for e in edges {
+                if d[u][e.to].map_or(true, |x| e.cost < x) {
+                    d[u][e.to] = Some(e.cost);
+                }
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                for j in 0..n {
+                    if let (Some(a), Some(b)) = (d[i][k], d[k][j]) {
+                        if d[i][j].map_or(true, |x| a + b < x) {
+                            d[i][j] = Some(a + b);
+                        }
+                    }
+                }
+            }
+        }
+        d
+    }
+
+    #[test]
+    fn dijkstra_with_path_test() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let n = rng.random_range(1..12);
+            let m = rng.random_range(0..30);
+            let graph = random_graph(&mut rng, n, m, 10);
+            let d = naive_dist(&graph);
+            let sources: Vec<usize> = (0..rng.random_range(1..=3))
+                .map(|_| rng.random_range(0..n))
+                .collect();
+            let res = dijkstra_with_path(&graph, &sources, None);
+            for (v, &dist) in res.dists().iter().enumerate() {
+                let expected = sources.iter().filter_map(|&s| d[s][v]).min();
+                assert_eq!(dist, expected);
+                match res.path_to(v) {
+                    Some(path) => {
+                        assert!(sources.contains(&path[0]));
+                        assert_eq!(*path.last().unwrap(), v);
+                        let len: u64 = path
+                            .windows(2)
+                            .map(|w| {
+                                let costs = graph[w[0]].iter().filter(|e| e.to == w[1]);
+                                costs.map(|e| e.cost).min().unwrap()
+                            })
+                            .sum();
+                        assert_eq!(Some(len), expected);
+                    }
+                    None => assert_eq!(expected, None),
+                }
+            }
+            assert_eq!(
+                dijkstra(&graph, sources[0]),
+                (0..n).map(|v| d[sources[0]][v]).collect::<Vec<_>>()
+            );
+
+            let target = rng.random_range(0..n);
+            let res = dijkstra_with_path(&graph, &sources, Some(target));
+            let expected = sources.iter().filter_map(|&s| d[s][target]).min();
+            assert_eq!(res.dist(target), expected);
+            assert_eq!(res.path_to(target).is_some(), expected.is_some());
+        }
+    }
+
+    #[test]
+    fn count_shortest_paths_test() {
+        let mut rng = StdRng::seed_from_u64(43);
+        for _ in 0..100 {
+            let n = rng.random_range(1..12);
+            let m = rng.random_range(0..40);
+            let graph = random_graph(&mut rng, n, m, 3);
+            let d = naive_dist(&graph)[0].clone();
+            // 距離の昇順に、最短経路 DAG 上で本数を足し合わせる
+            let mut order: Vec<usize> = (0..n).filter(|&v| d[v].is_some()).collect();
+            order.sort_by_key(|&v| d[v]);
+            let mut count = vec![0u64; n];
+            count[0] = 1;
+            for &v in order.iter() {
+                for u in 0..n {
+                    for e in graph[u].iter().filter(|e| e.to == v) {
+                        if d[u].is_some_and(|x| Some(x + e.cost) == d[v]) {
+                            count[v] += count[u];
+                        }
+                    }
+                }
+            }
+            let res = count_shortest_paths::<u64, ModInt998244353>(&graph, 0);
+            for v in 0..n {
+                let expected = d[v].map(|x| (x, ModInt998244353::new(count[v])));
+                assert_eq!(res[v], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn cost_types_test() {
+        // 0 -> 1 -> 3 と 0 -> 2 -> 4 -> 3 はどちらもコスト 4
+        let edges = [(0, 1, 2), (1, 3, 2), (0, 2, 1), (2, 4, 1), (4, 3, 2)];
+        let mut pair = vec![vec![]; 5];
+        let mut float = vec![vec![]; 5];
+        for &(u, v, c) in edges.iter() {
+            pair[u].push(Edge::new(v, (c as i64, 1u32)));
+            float[u].push(Edge::new(v, c as f64 / 2.0));
+        }
+        // 辺数も合わせて辞書順で最小にする
+        let res = dijkstra_with_path(&pair, &[0], None);
+        assert_eq!(res.dist(3), Some((4, 2)));
+        assert_eq!(res.path_to(3), Some(vec![0, 1, 3]));
+        assert_eq!(dijkstra(&float, 0)[4], Some(1.0));
+        let counts = count_shortest_paths::<f64, ModInt998244353>(&float, 0);
+        assert_eq!(counts[3], Some((2.0, ModInt998244353::new(2))));
     }
 }
@@ -0,0 +1,211 @@
+//! Floyd-Warshall 法
+//!
+//! 全点対の最短距離を$O(N^3)$で求め、経路も復元できる。負のコストの辺があってもよい
+//!
+//! 負閉路を経由できる頂点対の距離は `Distance::NegativeInfinity` になる。
+//! 負閉路に乗っていると分かった頂点は以降の緩和に使わないので、負閉路があっても
+//! 途中の値の絶対値は (辺のコストの絶対値の最大) × N 程度に収まる
+
+use super::bellman_ford::Distance;
+use super::dijkstra::{Cost, Edge, Graph};
+
+#[derive(Clone, Debug)]
+pub struct FloydWarshall<C> {
+    dist: Vec<Vec<Option<C>>>,
+    // next[i][j]: i から j への最短経路で i の次の頂点
+    next: Vec<Vec<usize>>,
+    // negative[i][j]: i から j へ負閉路を経由して行ける
+    negative: Vec<Vec<bool>>,
+}
+
+impl<C: Cost> FloydWarshall<C> {
+    pub fn new(graph: &Graph<C>) -> Self {
+        let n = graph.len();
+        let mut dist: Vec<Vec<Option<C>>> = vec![vec![None; n]; n];
+        let mut next = vec![vec![usize::MAX; n]; n];
+        for u in 0..n {
+            dist[u][u] = Some(C::ZERO);
+            next[u][u] = u;
+        }
+        for (u, edges) in graph.iter().enumerate() {
+            for &Edge { to, cost } in edges.iter() {
+                if dist[u][to].map_or(true, |x| cost < x) {
+                    dist[u][to] = Some(cost);
+                    next[u][to] = to;
+                }
+            }
+        }
+
+        // on_cycle[k]: k を経由する緩和の直前に、k を通る負閉路が見つかった
+        let mut on_cycle = vec![false; n];
+        for k in 0..n {
+            // 負閉路を何周もする値で緩和すると指数的に小さくなってしまうので、k は経由しない
+            if dist[k][k].unwrap() < C::ZERO {
+                on_cycle[k] = true;
+                continue;
+            }
+            for i in 0..n {
+                let dik = match dist[i][k] {
+                    Some(d) => d,
+                    None => continue,
+                };
+                for j in 0..n {
+                    if let Some(dkj) = dist[k][j] {
+                        let nd = dik.plus(dkj);
+                        if dist[i][j].map_or(true, |x| nd < x) {
+                            dist[i][j] = Some(nd);
+                            next[i][j] = next[i][k];
+                        }
+                    }
+                }
+            }
+        }
+
+        // 経由しなかった頂点を通る経路は dist に現れないので、到達可能性は別に求める
+        let mut reach = vec![vec![false; n]; n];
+        for (u, edges) in graph.iter().enumerate() {
+            reach[u][u] = true;
+            for e in edges.iter() {
+                reach[u][e.to] = true;
+            }
+        }
+        for k in 0..n {
+            let from_k = reach[k].clone();
+            for row in reach.iter_mut().filter(|row| row[k]) {
+                for (r, &x) in row.iter_mut().zip(from_k.iter()) {
+                    *r |= x;
+                }
+            }
+        }
+        let negative = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| (0..n).any(|k| on_cycle[k] && reach[i][k] && reach[k][j]))
+                    .collect()
+            })
+            .collect();
+        Self {
+            dist,
+            next,
+            negative,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.dist.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dist.is_empty()
+    }
+
+    /// i から j への最短距離
+    pub fn dist(&self, i: usize, j: usize) -> Distance<C> {
+        if self.negative[i][j] {
+            return Distance::NegativeInfinity;
+        }
+        self.dist[i][j].map_or(Distance::Unreachable, Distance::Finite)
+    }
+
+    /// 負閉路があるか
+    pub fn has_negative_cycle(&self) -> bool {
+        (0..self.len()).any(|i| self.negative[i][i])
+    }
+
+    /// i から j への最短経路の頂点列（両端を含む）。距離が有限でなければ `None`
+    pub fn path(&self, i: usize, j: usize) -> Option<Vec<usize>> {
+        self.dist(i, j).finite()?;
+        let mut path = vec![i];
+        while *path.last().unwrap() != j {
+            path.push(self.next[*path.last().unwrap()][j]);
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::bellman_ford::bellman_ford;
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn floyd_warshall_test() {
+        let mut rng = StdRng::seed_from_u64(46);
+        for _ in 0..200 {
+            let n = rng.random_range(1..8);
+            let mut graph = vec![vec![]; n];
+            for _ in 0..rng.random_range(0..15) {
+                let u = rng.random_range(0..n);
+                let v = rng.random_range(0..n);
+                let c: i64 = rng.random_range(-3..=10);
+                graph[u].push(Edge::new(v, c));
+            }
+            let fw = FloydWarshall::new(&graph);
+            let mut has_negative_cycle = false;
+            for i in 0..n {
+                let expected = bellman_ford(&graph, i);
+                for (j, &e) in expected.iter().enumerate() {
+                    assert_eq!(fw.dist(i, j), e);
+                    has_negative_cycle |= e == Distance::NegativeInfinity;
+                    match fw.path(i, j) {
+                        Some(path) => {
+                            assert_eq!((path[0], *path.last().unwrap()), (i, j));
+                            let len: i64 = path
+                                .windows(2)
+                                .map(|w| {
+                                    let costs = graph[w[0]].iter().filter(|e| e.to == w[1]);
+                                    costs.map(|e| e.cost).min().unwrap()
+                                })
+                                .sum();
+                            assert_eq!(Distance::Finite(len), e);
+                        }
+                        None => assert_eq!(e.finite(), None),
+                    }
+                }
+            }
+            assert_eq!(fw.has_negative_cycle(), has_negative_cycle);
+        }
+    }
+
+    #[test]
+    fn floyd_warshall_dense_negative_test() {
+        // 全頂点対に負の辺があると、負閉路を経由する緩和を続けた値は指数的に小さくなる
+        let n = 70;
+        let graph: Vec<Vec<Edge<i64>>> = (0..n)
+            .map(|u| {
+                (0..n)
+                    .filter(|&v| v != u)
+                    .map(|v| Edge::new(v, -1))
+                    .collect()
+            })
+            .collect();
+        let fw = FloydWarshall::new(&graph);
+        assert!(fw.has_negative_cycle());
+        for i in 0..n {
+            for j in 0..n {
+                assert_eq!(fw.dist(i, j), Distance::NegativeInfinity);
+                assert_eq!(fw.path(i, j), None);
+            }
+        }
+
+        let mut rng = StdRng::seed_from_u64(47);
+        for _ in 0..20 {
+            let n = rng.random_range(1..30);
+            let mut graph = vec![vec![]; n];
+            for _ in 0..rng.random_range(0..n * n) {
+                let u = rng.random_range(0..n);
+                let v = rng.random_range(0..n);
+                let c: i64 = rng.random_range(-1_000_000_000_000_000..=1_000_000_000_000_000);
+                graph[u].push(Edge::new(v, c));
+            }
+            let fw = FloydWarshall::new(&graph);
+            for i in 0..n {
+                for (j, &e) in bellman_ford(&graph, i).iter().enumerate() {
+                    assert_eq!(fw.dist(i, j), e);
+                }
+            }
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod dijkstra;
-pub mod topological_sort;
\ No newline at end of file
+pub mod bfs;
+pub mod bellman_ford;
+pub mod floyd_warshall;
+pub mod topological_sort;
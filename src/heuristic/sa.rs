@@ -119,7 +119,7 @@ impl<S: State, T: TemperatureSchedule> SimulatedAnnealing<S, T> {
                     let delta =
                         Self::score_to_f64(current_score) - Self::score_to_f64(neighbor_score);
                     let prob = (-delta / temperature).exp();
-                    rng.gen::<f64>() < prob
+                    rng.random::<f64>() < prob
                 }
             } else {
                 // 最小化問題
@@ -129,7 +129,7 @@ impl<S: State, T: TemperatureSchedule> SimulatedAnnealing<S, T> {
                     let delta =
                         Self::score_to_f64(neighbor_score) - Self::score_to_f64(current_score);
                     let prob = (-delta / temperature).exp();
-                    rng.gen::<f64>() < prob
+                    rng.random::<f64>() < prob
                 }
             };
 
@@ -163,9 +163,9 @@ impl<S: State, T: TemperatureSchedule> SimulatedAnnealing<S, T> {
 
     // スコアをf64に変換するヘルパー関数
     #[inline(always)]
-    fn score_to_f64<U: Copy>(score: U) -> f64
+    fn score_to_f64<U>(score: U) -> f64
     where
-        U: Into<f64>,
+        U: Copy + Into<f64>,
     {
         score.into()
     }
@@ -267,8 +267,8 @@ mod tests {
             let mut new_cities = self.cities.clone();
 
             // 2-opt操作
-            let i = rng.gen_range(0..new_cities.len());
-            let j = rng.gen_range(0..new_cities.len());
+            let i = rng.random_range(0..new_cities.len());
+            let j = rng.random_range(0..new_cities.len());
             if i != j {
                 let (start, end) = if i < j { (i, j) } else { (j, i) };
                 new_cities[start..=end].reverse();
@@ -282,7 +282,7 @@ mod tests {
     }
     #[test]
     fn test_tsp() {
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
 
         // 4都市のテスト用距離行列
         let distances = vec![
@@ -318,7 +318,7 @@ mod tests {
         }
 
         fn neighbor(&self, rng: &mut impl Rng) -> Self {
-            let delta = rng.gen_range(-5..=5);
+            let delta = rng.random_range(-5..=5);
             Self {
                 value: (self.value + delta).clamp(-100, 100),
             }
@@ -326,7 +326,7 @@ mod tests {
     }
     #[test]
     fn test_with_time_limit() {
-        let mut rng = rand::thread_rng();
+        let mut rng = rand::rng();
         let initial_state = IntegerSquare { value: 50 };
 
         // 100ms時間制限付き
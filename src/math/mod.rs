@@ -1 +1,2 @@
-pub mod prime;
\ No newline at end of file
+pub mod prime;
+pub mod modint;
@@ -0,0 +1,190 @@
+//! 法 `MOD` で剰余を取る整数
+//!
+//! `MOD` は素数であることを仮定する（`inv` と除算はフェルマーの小定理で計算する）
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModInt<const MOD: u32> {
+    val: u32,
+}
+
+pub type ModInt998244353 = ModInt<998_244_353>;
+pub type ModInt1000000007 = ModInt<1_000_000_007>;
+
+impl<const MOD: u32> ModInt<MOD> {
+    pub fn new(x: u64) -> Self {
+        Self {
+            val: (x % MOD as u64) as u32,
+        }
+    }
+
+    pub fn modulus() -> u32 {
+        MOD
+    }
+
+    pub fn val(&self) -> u32 {
+        self.val
+    }
+
+    pub fn pow(&self, mut exp: u64) -> Self {
+        let mut base = *self;
+        let mut res = Self::new(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        res
+    }
+
+    pub fn inv(&self) -> Self {
+        assert!(self.val != 0, "0 has no inverse");
+        self.pow(MOD as u64 - 2)
+    }
+}
+
+impl<const MOD: u32> fmt::Display for ModInt<MOD> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+impl<const MOD: u32> fmt::Debug for ModInt<MOD> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.val)
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($t: ty),*) => {$(
+        impl<const MOD: u32> From<$t> for ModInt<MOD> {
+            fn from(x: $t) -> Self {
+                Self::new(x as u64)
+            }
+        }
+    )*};
+}
+impl_from_unsigned!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_from_signed {
+    ($($t: ty),*) => {$(
+        impl<const MOD: u32> From<$t> for ModInt<MOD> {
+            fn from(x: $t) -> Self {
+                Self {
+                    val: (x as i64).rem_euclid(MOD as i64) as u32,
+                }
+            }
+        }
+    )*};
+}
+impl_from_signed!(i8, i16, i32, i64, isize);
+
+impl<const MOD: u32> Add for ModInt<MOD> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        // MOD が 2^31 を超えると u32 の和はオーバーフローするので u64 で足す
+        let mut val = self.val as u64 + rhs.val as u64;
+        if val >= MOD as u64 {
+            val -= MOD as u64;
+        }
+        Self { val: val as u32 }
+    }
+}
+
+impl<const MOD: u32> Sub for ModInt<MOD> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        let val = if self.val >= rhs.val {
+            self.val - rhs.val
+        } else {
+            MOD - rhs.val + self.val
+        };
+        Self { val }
+    }
+}
+
+impl<const MOD: u32> Mul for ModInt<MOD> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            val: (self.val as u64 * rhs.val as u64 % MOD as u64) as u32,
+        }
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl<const MOD: u32> Div for ModInt<MOD> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        self * rhs.inv()
+    }
+}
+
+impl<const MOD: u32> Neg for ModInt<MOD> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::default() - self
+    }
+}
+
+macro_rules! impl_assign {
+    ($($tr: ident, $f: ident, $op: tt);*) => {$(
+        impl<const MOD: u32> $tr for ModInt<MOD> {
+            fn $f(&mut self, rhs: Self) {
+                *self = *self $op rhs;
+            }
+        }
+    )*};
+}
+impl_assign!(AddAssign, add_assign, +; SubAssign, sub_assign, -; MulAssign, mul_assign, *; DivAssign, div_assign, /);
+
+impl<const MOD: u32> num::Zero for ModInt<MOD> {
+    fn zero() -> Self {
+        Self::default()
+    }
+    fn is_zero(&self) -> bool {
+        self.val == 0
+    }
+}
+
+impl<const MOD: u32> num::One for ModInt<MOD> {
+    fn one() -> Self {
+        Self::new(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modint_test() {
+        type Mint = ModInt998244353;
+        let a = Mint::new(998_244_352);
+        let b = Mint::new(2);
+        assert_eq!((a + b).val(), 1);
+        assert_eq!((b - a).val(), 3);
+        assert_eq!((a * a).val(), 1);
+        assert_eq!((Mint::new(1) / b * b).val(), 1);
+        assert_eq!((-b).val(), 998_244_351);
+        assert_eq!(Mint::from(-1i64), a);
+        assert_eq!(b.pow(10).val(), 1024);
+    }
+
+    #[test]
+    fn large_modulus_test() {
+        type Mint = ModInt<4_294_967_291>;
+        let a = Mint::new(4_294_967_290);
+        let b = Mint::new(3);
+        assert_eq!((a + a).val(), 4_294_967_289);
+        assert_eq!((a + b).val(), 2);
+        assert_eq!((b - a).val(), 4);
+        assert_eq!((-b).val(), 4_294_967_288);
+        assert_eq!((a * a).val(), 1);
+        assert_eq!((Mint::new(1) / b * b).val(), 1);
+    }
+}